@@ -33,26 +33,6 @@ impl Hash for AngularMomentum {
     }
 }
 
-impl From<u8> for AngularMomentum {
-    /// Panics for num > 10, as the angular momentum is only implemted up to M=10.
-    fn from(num: u8) -> Self {
-        match num {
-            0 => Self::S,
-            1 => Self::P,
-            2 => Self::D,
-            3 => Self::F,
-            4 => Self::G,
-            5 => Self::H,
-            6 => Self::I,
-            7 => Self::J,
-            8 => Self::K,
-            9 => Self::L,
-            10 => Self::M,
-            a => panic!("{} is not a valid angular momentum, only numbers between 0 and 10 are supported", a),
-        }
-    }
-}
-
 impl fmt::Display for AngularMomentum {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let symbol: &str = match self {
@@ -71,3 +51,51 @@ impl fmt::Display for AngularMomentum {
         write!(f, "{}", symbol)
     }
 }
+
+/// The angular momentum is represented by its letter in human-readable formats like JSON and
+/// by its number in binary formats like bincode. Both are accepted in human-readable formats.
+#[cfg(feature = "serde")]
+impl serde::Serialize for AngularMomentum {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_u8(*self as u8)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AngularMomentum {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AngularMomentumVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for AngularMomentumVisitor {
+            type Value = AngularMomentum;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an angular momentum as letter or number")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, letter: &str) -> Result<AngularMomentum, E> {
+                (0..=10u8)
+                    .filter_map(|l| AngularMomentum::try_from(l).ok())
+                    .find(|l| letter.eq_ignore_ascii_case(&l.to_string()))
+                    .ok_or_else(|| E::custom(format!("{} is not an angular momentum", letter)))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, l: u64) -> Result<AngularMomentum, E> {
+                u8::try_from(l)
+                    .ok()
+                    .and_then(|l| AngularMomentum::try_from(l).ok())
+                    .ok_or_else(|| E::custom(format!("{} is not an angular momentum", l)))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(AngularMomentumVisitor)
+        } else {
+            deserializer.deserialize_u8(AngularMomentumVisitor)
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! Portable archives of the local data, e.g. for clusters without internet access.
+//!
+//! An archive is a tar file that starts with an [ArchiveManifest], followed by the metadata of
+//! the BSE, the selected basis set files and their cached bibliographic records:
+//!
+//! ```no_run
+//! use ato::archive::{export_data, import_data, ArchiveSelection};
+//! // On a machine with access to the BSE:
+//! let selection = ArchiveSelection::new().family("dunning").name("def2-SVP");
+//! export_data("basis_sets.tar", &selection).unwrap();
+//! // On the cluster, with $ATO_DATA_PATH pointing to the shared data directory:
+//! let manifest = import_data("basis_sets.tar").unwrap();
+//! println!("{} basis sets imported", manifest.basis_sets.len());
+//! ```
+use crate::bse::http::BasisSetExchange;
+use crate::catalogue::Catalogue;
+use crate::files::{
+    data_path, find_data_path, latest_entry, references_suffix, DataLock, BASIS_SUBDIRECTORY,
+    BINCODE_BASIS_DICT, JSON_BASIS_DICT, JSON_METADATA,
+};
+use crate::manifest::{Manifest, ManifestEntry};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::process;
+
+/// Name of the manifest, which is the first file of an archive.
+pub const ARCHIVE_MANIFEST: &str = "ato_archive.json";
+/// Version of the layout of the archives that are written by this crate.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Content of an archive.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchiveManifest {
+    pub format_version: u32,
+    /// Version of the crate that wrote the archive.
+    pub ato_version: String,
+    /// All files of the archive except for the manifest itself.
+    pub files: Vec<ArchiveFile>,
+    /// Entries of the data manifest of the basis sets in the archive, including pinned versions.
+    pub basis_sets: BTreeMap<String, ManifestEntry>,
+}
+
+/// A file of an archive.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArchiveFile {
+    /// Path relative to the data directory, with `/` as separator.
+    pub path: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// SHA-256 of the content as hexadecimal string.
+    pub sha256: String,
+}
+
+/// Basis sets that are written to an archive.
+///
+/// A basis set is selected if it matches any of the names or families. Without names and
+/// families, all basis sets of the data directory are selected.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchiveSelection {
+    names: Vec<String>,
+    families: Vec<String>,
+}
+
+impl ArchiveSelection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select a basis set by any of its names. It has to be present in the data directory.
+    pub fn name(mut self, name: &str) -> Self {
+        self.names.push(name.to_owned());
+        self
+    }
+
+    /// Select all basis sets of a family, e.g. `dunning`, that are present in the data directory.
+    pub fn family(mut self, family: &str) -> Self {
+        self.families.push(family.to_owned());
+        self
+    }
+
+    fn is_empty(&self) -> bool {
+        self.names.is_empty() && self.families.is_empty()
+    }
+}
+
+/// Pack the metadata and the selected basis sets of the data directory into a tar archive.
+///
+/// Only basis sets that were downloaded completely are written, together with their pinned
+/// versions and cached bibliographic records. With several data roots, every basis set is
+/// taken from the root with its latest version and the other files are taken from the
+/// writable root first, see [crate::files::data_roots]. The latest version of a basis set that
+/// was selected by name has to be present in the data directory, see
+/// [crate::files::ensure_basis_exist]. The returned manifest is also the first file of the
+/// archive.
+///
+/// The [DataLock] is held during the export, so the files can not be replaced by a download
+/// between computing their checksums and writing them.
+pub fn export_data<P: AsRef<Path>>(
+    path: P,
+    selection: &ArchiveSelection,
+) -> Result<ArchiveManifest> {
+    let _lock = DataLock::acquire()?;
+    // Complete basis sets of all data roots, together with the root of the latest version.
+    let manifests = Manifest::read_all()?;
+    let mut available: BTreeMap<String, (PathBuf, ManifestEntry)> = BTreeMap::new();
+    for manifest in manifests.iter() {
+        for key in manifest.basis_sets.keys() {
+            if available.contains_key(key) {
+                continue;
+            }
+            if let Some(latest) = latest_entry(&manifests, key)? {
+                available.insert(key.clone(), latest);
+            }
+        }
+    }
+    let mut basis_sets: BTreeMap<String, ManifestEntry> = BTreeMap::new();
+    let mut ids: BTreeSet<String> = BTreeSet::new();
+    let mut sources: Vec<(String, PathBuf)> = Vec::new();
+    if selection.is_empty() {
+        for (key, (root, entry)) in available.iter() {
+            basis_sets.insert(key.clone(), entry.clone());
+            ids.insert(key.clone());
+            sources.push(basis_source(root, key));
+        }
+    } else {
+        let catalogue = Catalogue::from_metadata(
+            BasisSetExchange::read_metadata().context("Could not read the metadata")?,
+        );
+        let mut selected: BTreeSet<&str> = catalogue
+            .entries()
+            .filter(|entry| {
+                selection
+                    .families
+                    .iter()
+                    .any(|family| entry.family.eq_ignore_ascii_case(family))
+            })
+            .map(|entry| entry.id.as_str())
+            .collect();
+        for name in selection.names.iter() {
+            let id = catalogue.resolve(name)?.id.as_str();
+            if !available.contains_key(id) {
+                bail!(
+                    "The basis set {} is not present in the data directory",
+                    name
+                );
+            }
+            selected.insert(id);
+        }
+        for (key, (root, entry)) in available.iter() {
+            // Pinned versions are stored with the suffix of [crate::files::versioned_id].
+            let id = match key.rsplit_once("_v") {
+                Some((id, _)) if !selected.contains(key.as_str()) => id,
+                _ => key.as_str(),
+            };
+            if selected.contains(id) {
+                basis_sets.insert(key.clone(), entry.clone());
+                ids.insert(id.to_owned());
+                sources.push(basis_source(root, key));
+            }
+        }
+    }
+
+    let mut paths: Vec<(String, PathBuf)> = Vec::new();
+    for metadata in [BINCODE_BASIS_DICT, JSON_BASIS_DICT, JSON_METADATA] {
+        if let Some(source) = find_data_path(metadata)? {
+            paths.push((metadata.to_owned(), source));
+        }
+    }
+    if !paths.iter().any(|(path, _)| path == BINCODE_BASIS_DICT) {
+        bail!("The data directory does not contain the metadata of the BSE");
+    }
+    paths.append(&mut sources);
+    for id in ids.iter() {
+        let references = references_suffix(id);
+        if let Some(source) = find_data_path(&references)? {
+            paths.push((references, source));
+        }
+    }
+
+    // The complete data does not fit into memory, so the files are read twice.
+    let mut files: Vec<ArchiveFile> = Vec::new();
+    for (path, source) in paths.iter() {
+        let data = read_data(source)?;
+        files.push(ArchiveFile {
+            path: path.clone(),
+            size: data.len() as u64,
+            sha256: sha256(&data),
+        });
+    }
+    let archive_manifest = ArchiveManifest {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        ato_version: env!("CARGO_PKG_VERSION").to_owned(),
+        files,
+        basis_sets,
+    };
+
+    let output = fs::File::create(path.as_ref()).context("Could not create the archive")?;
+    let mut builder = tar::Builder::new(output);
+    append(
+        &mut builder,
+        ARCHIVE_MANIFEST,
+        &serde_json::to_vec_pretty(&archive_manifest)?,
+    )?;
+    for (path, source) in paths.iter() {
+        append(&mut builder, path, &read_data(source)?)?;
+    }
+    builder
+        .into_inner()
+        .context("Could not write the archive")?
+        .sync_all()?;
+    Ok(archive_manifest)
+}
+
+/// Read the manifest of an archive without importing it.
+pub fn read_archive_manifest<P: AsRef<Path>>(path: P) -> Result<ArchiveManifest> {
+    let input = fs::File::open(path.as_ref()).context("Could not open the archive")?;
+    let mut archive = tar::Archive::new(input);
+    let mut entries = archive.entries().context("Could not read the archive")?;
+    archive_manifest(&mut entries)
+}
+
+/// Unpack an archive that was written by [export_data] into the data directory.
+///
+/// All files are checked against the sizes and checksums of the archive manifest before any of
+/// them is moved into the data directory, so a corrupted archive leaves the data untouched. The
+/// metadata of the archive replaces the local metadata and the basis sets are added to the data
+/// manifest. Basis sets of the data directory that are not part of the archive are kept.
+pub fn import_data<P: AsRef<Path>>(path: P) -> Result<ArchiveManifest> {
+    let _lock = DataLock::acquire()?;
+    let input = fs::File::open(path.as_ref()).context("Could not open the archive")?;
+    let mut archive = tar::Archive::new(input);
+    let mut entries = archive.entries().context("Could not read the archive")?;
+    let archive_manifest = archive_manifest(&mut entries)?;
+    let expected: BTreeMap<&str, &ArchiveFile> = archive_manifest
+        .files
+        .iter()
+        .map(|file| (file.path.as_str(), file))
+        .collect();
+    for file in archive_manifest.files.iter() {
+        let is_relative = Path::new(&file.path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if !is_relative {
+            bail!("The archive contains the invalid path {}", file.path);
+        }
+    }
+
+    // The files are verified in a staging directory inside of the data directory, so that they
+    // can be renamed into place afterwards.
+    let staging = data_path(&format!(".import.{}", process::id()))?;
+    let result = unpack(&mut entries, &expected, &staging).and_then(|_| {
+        for file in archive_manifest.files.iter() {
+            let target = data_path(&file.path)?;
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).context("Could not create data dir")?;
+            }
+            fs::rename(staging.join(&file.path), &target)
+                .with_context(|| format!("Could not move {} into the data directory", file.path))?;
+        }
+        Ok(())
+    });
+    let _ = fs::remove_dir_all(&staging);
+    result?;
+
+    let mut manifest = Manifest::read()?;
+    for (key, entry) in archive_manifest.basis_sets.iter() {
+        manifest.insert(key, entry.size, &entry.version);
+    }
+    manifest.write()?;
+    for key in archive_manifest.basis_sets.keys() {
+        if !manifest.is_complete(key)? {
+            bail!("The basis set {} is incomplete after the import", key);
+        }
+    }
+    Ok(archive_manifest)
+}
+
+/// Path of a basis set file in the archive and in the data root that contains it.
+fn basis_source(root: &Path, key: &str) -> (String, PathBuf) {
+    let path = format!("{}{}.json", BASIS_SUBDIRECTORY, key);
+    let source = root.join(&path);
+    (path, source)
+}
+
+fn read_data(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).with_context(|| format!("Could not read {}", path.display()))
+}
+
+fn sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+fn append<W: std::io::Write>(builder: &mut tar::Builder<W>, path: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    builder
+        .append_data(&mut header, path, data)
+        .with_context(|| format!("Could not write {} to the archive", path))
+}
+
+fn archive_manifest<R: Read>(entries: &mut tar::Entries<R>) -> Result<ArchiveManifest> {
+    let mut entry = match entries.next() {
+        Some(entry) => entry.context("Could not read the archive")?,
+        None => bail!("The archive is empty"),
+    };
+    if entry.path()?.to_str() != Some(ARCHIVE_MANIFEST) {
+        bail!("The archive does not start with {}", ARCHIVE_MANIFEST);
+    }
+    let mut data: Vec<u8> = Vec::new();
+    entry.read_to_end(&mut data)?;
+    let manifest: ArchiveManifest =
+        serde_json::from_slice(&data).context("Could not deserialize the archive manifest")?;
+    if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+        bail!(
+            "The archive has the format version {}, but only {} is supported",
+            manifest.format_version,
+            ARCHIVE_FORMAT_VERSION
+        );
+    }
+    Ok(manifest)
+}
+
+/// Write all files of the archive to the staging directory and verify them.
+fn unpack<R: Read>(
+    entries: &mut tar::Entries<R>,
+    expected: &BTreeMap<&str, &ArchiveFile>,
+    staging: &Path,
+) -> Result<()> {
+    let mut unpacked: BTreeSet<String> = BTreeSet::new();
+    for entry in entries {
+        let mut entry = entry.context("Could not read the archive")?;
+        let path = entry
+            .path()?
+            .to_str()
+            .context("The archive contains an invalid path")?
+            .to_owned();
+        let file = match expected.get(path.as_str()) {
+            Some(file) => file,
+            None => bail!("The file {} is not listed in the archive manifest", path),
+        };
+        let mut data: Vec<u8> = Vec::new();
+        entry.read_to_end(&mut data)?;
+        if data.len() as u64 != file.size || sha256(&data) != file.sha256 {
+            bail!(
+                "The checksum of {} does not match the archive manifest",
+                path
+            );
+        }
+        let target = staging.join(&path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).context("Could not create staging dir")?;
+        }
+        fs::write(&target, &data).with_context(|| format!("Could not write {}", path))?;
+        unpacked.insert(path);
+    }
+    for path in expected.keys() {
+        if !unpacked.contains(*path) {
+            bail!("The file {} is missing in the archive", path);
+        }
+    }
+    Ok(())
+}
@@ -5,6 +5,7 @@ use std::convert::TryFrom;
 
 /// Type that specifies an atomic orbital by its three quantum numbers and holds its energy
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AtomicOrbital {
     pub n: Shell,
     pub l: AngularMomentum,
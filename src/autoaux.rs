@@ -0,0 +1,259 @@
+//! Generation of fitting basis sets for orbital basis sets without a companion on the BSE.
+use crate::angular_momentum::AngularMomentum;
+use crate::basis_function::{BasisFunction, FunctionType};
+use crate::basis_set::BasisSet;
+use crate::elements::Element;
+use crate::error::AtoError;
+use crate::formats::gaussian_elements;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+
+/// Ratios of the even-tempered exponents for s, p, d, ... functions. Higher angular momenta
+/// use the last value.
+pub const DEFAULT_BETAS: [f64; 7] = [1.8, 2.0, 2.2, 2.2, 2.2, 2.3, 3.0];
+
+/// Generator of Coulomb and RI fitting basis sets in the spirit of AutoAux
+/// (Stoychev, Auer and Neese, J. Chem. Theory Comput. 13, 554 (2017)).
+///
+/// For every angular momentum L of the fitting basis, the range of the exponents of all
+/// products of two orbital primitives with angular momenta l1 and l2, where
+/// `|l1 - l2| <= L <= l1 + l2`, is covered by uncontracted even-tempered shells. The highest
+/// angular momentum is the one of the orbital basis plus an increment, but at least twice the
+/// angular momentum of the occupied shells of the atom.
+///
+/// ```
+/// use ato::autoaux::AutoAux;
+/// use ato::elements::Element;
+/// use ato::formats::{gaussian94, BasisFormat, Gaussian94};
+/// let basis = gaussian94::read("mini", "H 0\nS 1 1.00\n 3.0 1.0\nS 1 1.00\n 0.5 1.0\nP 1 1.00\n 0.8 1.0\n****\n").unwrap();
+/// let auxiliary = AutoAux::new().generate(&basis, &[Element::H]).unwrap();
+/// assert_eq!(auxiliary.role, "rifit");
+/// assert!(Gaussian94.write(&auxiliary).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct AutoAux {
+    betas: Vec<f64>,
+    l_increment: u8,
+}
+
+impl Default for AutoAux {
+    fn default() -> Self {
+        Self {
+            betas: DEFAULT_BETAS.to_vec(),
+            l_increment: 1,
+        }
+    }
+}
+
+impl AutoAux {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ratios of the even-tempered exponents by angular momentum, see [DEFAULT_BETAS].
+    pub fn betas(mut self, betas: &[f64]) -> Self {
+        self.betas = betas.to_vec();
+        self
+    }
+
+    /// Increment of the highest angular momentum of the orbital basis set.
+    pub fn l_increment(mut self, l_increment: u8) -> Self {
+        self.l_increment = l_increment;
+        self
+    }
+
+    /// Generate the fitting basis set of the given elements with the role `rifit`.
+    ///
+    /// All elements must have Gaussian basis functions in the orbital basis set.
+    pub fn generate(&self, basis: &BasisSet, elements: &[Element]) -> Result<BasisSet, AtoError> {
+        if self.betas.is_empty()
+            || self
+                .betas
+                .iter()
+                .any(|beta| !beta.is_finite() || *beta <= 1.0)
+        {
+            return Err(AtoError::InvalidArgument(
+                "the ratios of the even-tempered exponents must be larger than one".to_owned(),
+            ));
+        }
+        let mut basis_functions: HashMap<Element, Vec<BasisFunction>> = HashMap::new();
+        for element in gaussian_elements(basis, elements)? {
+            let functions = basis
+                .basis_functions
+                .get(&element)
+                .filter(|functions| !functions.is_empty())
+                .ok_or_else(|| AtoError::MissingElement {
+                    basis: basis.name.clone(),
+                    element,
+                })?;
+            let positive = |exponent: &f64| exponent.is_finite() && *exponent > 0.0;
+            if !functions
+                .iter()
+                .all(|function| function.exponents.iter().all(positive))
+            {
+                return Err(AtoError::InvalidArgument(format!(
+                    "the exponents of {} in {} must be positive",
+                    element.symbol(),
+                    basis.name
+                )));
+            }
+            basis_functions.insert(element, self.shells(element, functions));
+        }
+        let name = format!("{}-AutoAux", basis.name);
+        Ok(BasisSet {
+            id: name.to_lowercase(),
+            names: vec![name.clone()],
+            description: format!("Auxiliary basis set generated from {}", basis.name),
+            family: basis.family.clone(),
+            role: "rifit".to_owned(),
+            function_types: vec!["gto_spherical".to_owned()],
+            basis_functions,
+            name,
+            ..BasisSet::default()
+        })
+    }
+
+    /// Even-tempered shells of one element, ordered by angular momentum and descending
+    /// exponents.
+    fn shells(&self, element: Element, functions: &[BasisFunction]) -> Vec<BasisFunction> {
+        // Smallest and largest primitive exponent of every angular momentum.
+        let mut ranges: BTreeMap<u8, (f64, f64)> = BTreeMap::new();
+        for function in functions.iter() {
+            for exponent in function.exponents.iter() {
+                let range = ranges
+                    .entry(function.l as u8)
+                    .or_insert((f64::INFINITY, 0.0));
+                range.0 = range.0.min(*exponent);
+                range.1 = range.1.max(*exponent);
+            }
+        }
+        let l_max = ranges.keys().copied().max().unwrap_or(0);
+        let l_aux = l_max
+            .saturating_add(self.l_increment)
+            .max(2 * occupied_l(element))
+            .min(2 * l_max)
+            .min(10);
+
+        let mut shells: Vec<BasisFunction> = Vec::new();
+        for big_l in 0..=l_aux {
+            let products = ranges.iter().flat_map(|(l1, r1)| {
+                ranges
+                    .iter()
+                    .filter(move |(l2, _)| l1.abs_diff(**l2) <= big_l && l1 + *l2 >= big_l)
+                    .map(move |(_, r2)| (r1.0 + r2.0, r1.1 + r2.1))
+            });
+            let (low, high) = products.fold((f64::INFINITY, 0.0f64), |(low, high), (a, b)| {
+                (low.min(a), high.max(b))
+            });
+            // There are no products for this angular momentum.
+            if !low.is_finite() {
+                continue;
+            }
+            let beta = self.betas[(big_l as usize).min(self.betas.len() - 1)];
+            let l = AngularMomentum::try_from(big_l).unwrap();
+            for exponent in even_tempered(low, high, beta) {
+                shells.push(BasisFunction::new(
+                    l,
+                    vec![exponent],
+                    vec![1.0],
+                    FunctionType::Spherical,
+                ));
+            }
+        }
+        shells
+    }
+}
+
+/// Exponents with the ratio `beta` that cover the range symmetrically, in descending order.
+fn even_tempered(low: f64, high: f64, beta: f64) -> Vec<f64> {
+    let n = ((high / low).ln() / beta.ln()).ceil() as i32 + 1;
+    let smallest = (low * high).sqrt() / beta.powf((n - 1) as f64 / 2.0);
+    (0..n).rev().map(|k| smallest * beta.powi(k)).collect()
+}
+
+/// Highest angular momentum of the occupied shells of the free atom.
+fn occupied_l(element: Element) -> u8 {
+    match element.number() {
+        1..=4 => 0,
+        5..=20 => 1,
+        21..=57 => 2,
+        _ => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::angular_momentum::AngularMomentum;
+    use crate::autoaux::{even_tempered, AutoAux};
+    use crate::elements::Element;
+    use crate::error::AtoError;
+    use crate::formats::tests::basis;
+
+    #[test]
+    fn test_even_tempered() {
+        let exponents = even_tempered(1.0, 4.0, 2.0);
+        assert_eq!(exponents, vec![4.0, 2.0, 1.0]);
+        let exponents = even_tempered(1.0, 3.0, 2.0);
+        assert_eq!(exponents.len(), 3);
+        assert!((exponents[0] * exponents[2] - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_generate() {
+        let basis = basis();
+        let auxiliary = AutoAux::new()
+            .generate(&basis, &[Element::C, Element::H, Element::C])
+            .unwrap();
+        assert_eq!(auxiliary.name, "test-AutoAux");
+        assert_eq!(auxiliary.role, "rifit");
+        assert_eq!(auxiliary.basis_functions.len(), 2);
+
+        // Hydrogen has s and p functions, the s range is 2 * 0.727 to 2 * 13.01.
+        let functions = &auxiliary.basis_functions[&Element::H];
+        assert_eq!(functions.last().unwrap().l, AngularMomentum::D);
+        let s: Vec<f64> = functions
+            .iter()
+            .filter(|f| f.l == AngularMomentum::S)
+            .map(|f| f.exponents[0])
+            .collect();
+        assert!(s[0] >= 26.02 && *s.last().unwrap() <= 1.454);
+        assert!(s.windows(2).all(|w| (w[0] / w[1] - 1.8).abs() < 1e-12));
+
+        // Carbon has d functions, so the fitting basis goes up to f.
+        let l_max = auxiliary.basis_functions[&Element::C]
+            .iter()
+            .map(|f| f.l as u8)
+            .max();
+        assert_eq!(l_max, Some(3));
+
+        assert!(matches!(
+            AutoAux::new().generate(&basis, &[Element::I]),
+            Err(AtoError::MissingElement { .. })
+        ));
+        assert!(AutoAux::new()
+            .betas(&[1.0])
+            .generate(&basis, &[Element::H])
+            .is_err());
+
+        // The angular momentum is limited by the one of the orbital basis set.
+        let auxiliary = AutoAux::new()
+            .l_increment(u8::MAX)
+            .generate(&basis, &[Element::C])
+            .unwrap();
+        assert!(auxiliary.basis_functions[&Element::C]
+            .iter()
+            .all(|f| f.l as u8 <= 4));
+    }
+
+    #[test]
+    fn test_invalid_exponents() {
+        for exponent in [0.0, -1.0, f64::NAN] {
+            let mut basis = basis();
+            basis.basis_functions.get_mut(&Element::H).unwrap()[0].exponents[0] = exponent;
+            assert!(matches!(
+                AutoAux::new().generate(&basis, &[Element::H]),
+                Err(AtoError::InvalidArgument(_))
+            ));
+        }
+    }
+}
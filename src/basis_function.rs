@@ -1,8 +1,140 @@
 use crate::angular_momentum::AngularMomentum;
 
+/// Form in which the functions of a shell are used.
+///
+/// A shell with the angular momentum `l` has `2l + 1` spherical (pure) functions but
+/// `(l + 1)(l + 2) / 2` Cartesian functions, e.g. 5 or 6 d functions.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum FunctionType {
+    Spherical,
+    Cartesian,
+}
+
+impl FunctionType {
+    /// Name of the function type as it is used in the BSE JSON format.
+    pub fn bse_name(&self) -> &'static str {
+        match self {
+            FunctionType::Spherical => "gto_spherical",
+            FunctionType::Cartesian => "gto_cartesian",
+        }
+    }
+}
+
+/// A single component of a shell, that is one atomic orbital.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Component {
+    /// Real solid harmonic with the magnetic quantum number m.
+    Spherical(i8),
+    /// Cartesian function x^i y^j z^k with the powers (i, j, k).
+    Cartesian(u8, u8, u8),
+}
+
+/// The values of a shell or ECP as they were written in the BSE data.
+///
+/// They are used to write unchanged values exactly as they were read.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OriginalValues {
+    /// Function type or ECP type, e.g. `gto` or `scalar_ecp`.
+    pub kind: String,
+    /// Form in which the shell was read, which may differ from its current form.
+    pub function_type: Option<FunctionType>,
+    pub exponents: Vec<String>,
+    pub coefficients: Vec<String>,
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BasisFunction {
     pub l: AngularMomentum,
     pub exponents: Vec<f64>,
     pub coefficients: Vec<f64>,
+    pub function_type: FunctionType,
+    /// Region of the shell in the BSE data, e.g. `valence` or `polarization`.
+    pub region: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) original: Option<OriginalValues>,
+}
+
+impl BasisFunction {
+    pub fn new(
+        l: AngularMomentum,
+        exponents: Vec<f64>,
+        coefficients: Vec<f64>,
+        function_type: FunctionType,
+    ) -> Self {
+        Self {
+            l,
+            exponents,
+            coefficients,
+            function_type,
+            region: String::new(),
+            original: None,
+        }
+    }
+
+    /// Number of atomic orbitals of the shell.
+    pub fn n_functions(&self) -> usize {
+        let l = self.l as usize;
+        match self.function_type {
+            FunctionType::Spherical => 2 * l + 1,
+            FunctionType::Cartesian => (l + 1) * (l + 2) / 2,
+        }
+    }
+
+    /// The atomic orbitals of the shell.
+    ///
+    /// Spherical components are ordered by m from -l to l and Cartesian components in the
+    /// canonical order xx, xy, xz, yy, yz, zz.
+    pub fn components(&self) -> Vec<Component> {
+        let l = self.l as u8;
+        match self.function_type {
+            FunctionType::Spherical => (-(l as i8)..=l as i8).map(Component::Spherical).collect(),
+            FunctionType::Cartesian => (0..=l)
+                .rev()
+                .flat_map(|i| {
+                    (0..=l - i)
+                        .rev()
+                        .map(move |j| Component::Cartesian(i, j, l - i - j))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::angular_momentum::AngularMomentum;
+    use crate::basis_function::{BasisFunction, Component, FunctionType};
+
+    #[test]
+    fn test_components() {
+        let mut function = BasisFunction::new(
+            AngularMomentum::D,
+            vec![1.0],
+            vec![1.0],
+            FunctionType::Cartesian,
+        );
+        assert_eq!(function.n_functions(), 6);
+        assert_eq!(
+            function.components(),
+            vec![
+                Component::Cartesian(2, 0, 0),
+                Component::Cartesian(1, 1, 0),
+                Component::Cartesian(1, 0, 1),
+                Component::Cartesian(0, 2, 0),
+                Component::Cartesian(0, 1, 1),
+                Component::Cartesian(0, 0, 2)
+            ]
+        );
+        function.function_type = FunctionType::Spherical;
+        assert_eq!(function.n_functions(), 5);
+        assert_eq!(function.components().len(), 5);
+        assert_eq!(function.components()[0], Component::Spherical(-2));
+    }
 }
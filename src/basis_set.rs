@@ -1,67 +1,337 @@
 use crate::angular_momentum::AngularMomentum;
-use crate::basis_function::BasisFunction;
+use crate::basis_function::{BasisFunction, FunctionType, OriginalValues};
 use crate::bse::basisset_json::*;
 use crate::bse::http::BasisSetExchange;
+use crate::bse::source::BasisSource;
+use crate::catalogue::Catalogue;
+use crate::ecp::{EcpProjector, EcpType, EffectiveCorePotential};
 use crate::elements::Element;
-use std::collections::HashMap;
+use crate::error::AtoError;
+use crate::files::update_references;
+use crate::references::{self, bse_reference, CitationFormat, Reference};
+use crate::slater_function::SlaterFunction;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BasisSet {
     pub name: String,
+    /// Further names of the basis set, e.g. `6-31G(d)` for `6-31G*`.
+    pub names: Vec<String>,
     pub description: String,
+    /// Identifier of the basis set within its source, e.g. the BSE id `6-31g_st_`.
+    pub id: String,
+    /// Version of the basis set data on the BSE.
+    pub version: String,
+    pub revision_date: String,
+    pub revision_description: String,
+    pub family: String,
+    /// Role of the basis set, e.g. `orbital` or `jkfit`.
+    pub role: String,
+    pub tags: Vec<String>,
+    /// Function types of the BSE data, e.g. `gto` and `gto_spherical`.
+    pub function_types: Vec<String>,
+    /// Names of the auxiliary basis sets by their role.
+    pub auxiliaries: BTreeMap<String, Vec<String>>,
     pub basis_functions: HashMap<Element, Vec<BasisFunction>>,
+    /// Effective core potentials of the elements whose core electrons are replaced.
+    pub ecps: HashMap<Element, EffectiveCorePotential>,
+    /// Slater-type shells of the elements, which are not part of the `basis_functions`.
+    pub slater_functions: HashMap<Element, Vec<SlaterFunction>>,
+    /// Literature references of the data of the elements.
+    pub references: HashMap<Element, Vec<InputReferences>>,
 }
 
-impl From<InputData> for BasisSet {
-    fn from(data: InputData) -> Self {
+impl TryFrom<InputData> for BasisSet {
+    type Error = AtoError;
+
+    fn try_from(data: InputData) -> Result<Self, Self::Error> {
         // The HashMap is initialized.
         let mut bfs: HashMap<Element, Vec<BasisFunction>> = HashMap::new();
+        let mut ecps: HashMap<Element, EffectiveCorePotential> = HashMap::new();
+        let mut stos: HashMap<Element, Vec<SlaterFunction>> = HashMap::new();
+        let mut references: HashMap<Element, Vec<InputReferences>> = HashMap::new();
+        // Shells that are only marked as `gto` follow the function types of the basis set.
+        let default_type = if data.function_types.iter().any(|t| t == "gto_cartesian")
+            && !data.function_types.iter().any(|t| t == "gto_spherical")
+        {
+            FunctionType::Cartesian
+        } else {
+            FunctionType::Spherical
+        };
         for (element, shells) in data.elements.iter() {
             // The corresponding Element is created.
-            let el: Element = Element::from(*element as u8);
+            let el: Element = match u8::try_from(*element) {
+                Ok(number) if (1..Element::count()).contains(&number) => Element::from(number),
+                _ => {
+                    return Err(AtoError::Parse(format!(
+                        "{} is not an atomic number",
+                        element
+                    )))
+                }
+            };
 
             // The BasisFunctions are created.
             let mut functions: Vec<BasisFunction> = Vec::new();
+            let mut slater_functions: Vec<SlaterFunction> = Vec::new();
 
             // Iteration over all shells.
-            //for shell in shells.electron_shells.iter() {}
-            let shell: &InputShell = &shells.electron_shells[shells.electron_shells.len() - 1];
-
-            // The exponents are the same for all angular momenta.
-            let exponents: Vec<f64> = shell
-                .exponents
-                .iter()
-                .map(|x| x.parse::<f64>().unwrap())
-                .collect();
-
-            // Iteration over all angular momenta.
-            for (l, c) in shell.angular_momentum.iter().zip(shell.coefficients.iter()) {
-                // The coefficients are converted to floats.
-                let coefficients: Vec<f64> = c.iter().map(|x| x.parse::<f64>().unwrap()).collect();
-
-                // Add the new BasisFunction
-                functions.push(BasisFunction {
-                    l: AngularMomentum::try_from(*l as u8).unwrap(),
-                    exponents: exponents.clone(),
-                    coefficients,
-                });
+            for shell in shells.electron_shells.iter() {
+                if shell.function_type == "sto" {
+                    slater_functions.append(&mut slater_functions_from_shell(shell)?);
+                } else {
+                    functions.append(&mut basis_functions_from_shell(shell, default_type)?);
+                }
             }
 
-            bfs.insert(el, functions);
+            references.insert(el, shells.references.clone());
+            let ecp = ecp_from_element(shells)?;
+            // Pure ECP or STO basis sets do not contain any Gaussian basis functions.
+            let gaussian = !functions.is_empty() || (ecp.is_none() && slater_functions.is_empty());
+            if let Some(ecp) = ecp {
+                ecps.insert(el, ecp);
+            }
+            if !slater_functions.is_empty() {
+                stos.insert(el, slater_functions);
+            }
+            if gaussian {
+                bfs.insert(el, functions);
+            }
         }
-        Self {
+        Ok(Self {
+            id: data.name.to_lowercase(),
             name: data.name,
+            names: data.names,
             description: data.description,
+            version: data.version,
+            revision_date: data.revision_date,
+            revision_description: data.revision_description,
+            family: data.family,
+            role: data.role,
+            tags: data.tags,
+            function_types: data.function_types,
+            auxiliaries: data
+                .auxiliaries
+                .iter()
+                .map(|(role, auxiliary)| (role.clone(), auxiliary.names()))
+                .collect(),
             basis_functions: bfs,
+            ecps,
+            slater_functions: stos,
+            references,
+        })
+    }
+}
+
+/// Convert a single BSE shell into its contracted basis functions.
+///
+/// Every column of the coefficient matrix is one contraction. If the shell lists exactly one
+/// angular momentum, all columns belong to it (general contraction). Otherwise the i-th column
+/// belongs to the i-th angular momentum, as for the Pople SP shells with `[0, 1]`.
+fn basis_functions_from_shell(
+    shell: &InputShell,
+    default_type: FunctionType,
+) -> Result<Vec<BasisFunction>, AtoError> {
+    let function_type = match shell.function_type.as_str() {
+        "gto" => default_type,
+        "gto_spherical" => FunctionType::Spherical,
+        "gto_cartesian" => FunctionType::Cartesian,
+        other => return Err(AtoError::UnsupportedFunctionType(other.to_owned())),
+    };
+    if shell.angular_momentum.len() != 1 && shell.angular_momentum.len() != shell.coefficients.len()
+    {
+        return Err(AtoError::Parse(format!(
+            "a shell with the angular momenta {:?} has {} coefficient columns",
+            shell.angular_momentum,
+            shell.coefficients.len()
+        )));
+    }
+
+    // The exponents are the same for all contractions of the shell.
+    let exponents: Vec<f64> = parse_floats(&shell.exponents)?;
+
+    shell
+        .coefficients
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let l: usize = if shell.angular_momentum.len() == 1 {
+                shell.angular_momentum[0]
+            } else {
+                shell.angular_momentum[i]
+            };
+            // The coefficients are converted to floats.
+            Ok(BasisFunction {
+                l: angular_momentum(l)?,
+                exponents: exponents.clone(),
+                coefficients: parse_floats(c)?,
+                function_type,
+                region: shell.region.clone(),
+                original: Some(OriginalValues {
+                    kind: shell.function_type.clone(),
+                    function_type: Some(function_type),
+                    exponents: shell.exponents.clone(),
+                    coefficients: c.clone(),
+                }),
+            })
+        })
+        .collect()
+}
+
+/// Convert a Slater-type shell of the BSE.
+///
+/// The `r_exponents` of the shell are the principal quantum numbers of the primitives. The
+/// columns of the coefficients are assigned to the angular momenta like for Gaussian shells.
+fn slater_functions_from_shell(shell: &InputShell) -> Result<Vec<SlaterFunction>, AtoError> {
+    if shell.angular_momentum.len() != 1 && shell.angular_momentum.len() != shell.coefficients.len()
+    {
+        return Err(AtoError::Parse(format!(
+            "a shell with the angular momenta {:?} has {} coefficient columns",
+            shell.angular_momentum,
+            shell.coefficients.len()
+        )));
+    }
+    let exponents: Vec<f64> = parse_floats(&shell.exponents)?;
+    if shell.r_exponents.len() != exponents.len() {
+        return Err(AtoError::Parse(format!(
+            "a Slater shell has {} r-exponents but {} exponents",
+            shell.r_exponents.len(),
+            exponents.len()
+        )));
+    }
+    let principal_numbers: Vec<u8> = shell
+        .r_exponents
+        .iter()
+        .map(|n| {
+            u8::try_from(*n)
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| AtoError::Parse(format!("{} is not a principal quantum number", n)))
+        })
+        .collect::<Result<_, _>>()?;
+
+    shell
+        .coefficients
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let l: usize = if shell.angular_momentum.len() == 1 {
+                shell.angular_momentum[0]
+            } else {
+                shell.angular_momentum[i]
+            };
+            let l = angular_momentum(l)?;
+            if let Some(n) = principal_numbers
+                .iter()
+                .find(|n| **n as usize <= l as usize)
+            {
+                return Err(AtoError::Parse(format!(
+                    "the principal quantum number {} is too small for the angular momentum {}",
+                    n, l
+                )));
+            }
+            Ok(SlaterFunction {
+                l,
+                principal_numbers: principal_numbers.clone(),
+                exponents: exponents.clone(),
+                coefficients: parse_floats(c)?,
+                region: shell.region.clone(),
+                original: Some(OriginalValues {
+                    kind: shell.function_type.clone(),
+                    function_type: None,
+                    exponents: shell.exponents.clone(),
+                    coefficients: c.clone(),
+                }),
+            })
+        })
+        .collect()
+}
+
+/// Convert the ECP of a BSE element, if it has one.
+///
+/// Like for the shells, every row of the coefficients belongs to the angular momentum at the
+/// same position.
+fn ecp_from_element(element: &InputElement) -> Result<Option<EffectiveCorePotential>, AtoError> {
+    if element.ecp_potentials.is_empty() {
+        return Ok(None);
+    }
+    let mut projectors: Vec<EcpProjector> = Vec::new();
+    for potential in element.ecp_potentials.iter() {
+        let kind = match potential.ecp_type.as_str() {
+            "scalar_ecp" => EcpType::Scalar,
+            "spinorbit_ecp" => EcpType::SpinOrbit,
+            other => return Err(AtoError::UnsupportedFunctionType(other.to_owned())),
+        };
+        if potential.angular_momentum.len() != potential.coefficients.len() {
+            return Err(AtoError::Parse(format!(
+                "an ECP with the angular momenta {:?} has {} coefficient rows",
+                potential.angular_momentum,
+                potential.coefficients.len()
+            )));
+        }
+        let exponents: Vec<f64> = parse_floats(&potential.gaussian_exponents)?;
+        if potential.r_exponents.len() != exponents.len() {
+            return Err(AtoError::Parse(format!(
+                "an ECP has {} r-exponents but {} gaussian exponents",
+                potential.r_exponents.len(),
+                exponents.len()
+            )));
+        }
+        for (l, c) in potential
+            .angular_momentum
+            .iter()
+            .zip(potential.coefficients.iter())
+        {
+            projectors.push(EcpProjector {
+                l: angular_momentum(*l)?,
+                kind,
+                r_exponents: potential.r_exponents.clone(),
+                exponents: exponents.clone(),
+                coefficients: parse_floats(c)?,
+                original: Some(OriginalValues {
+                    kind: potential.ecp_type.clone(),
+                    function_type: None,
+                    exponents: potential.gaussian_exponents.clone(),
+                    coefficients: c.clone(),
+                }),
+            });
         }
     }
+    Ok(Some(EffectiveCorePotential {
+        core_electrons: element.ecp_electrons.unwrap_or(0),
+        projectors,
+    }))
+}
+
+/// Convert an angular momentum of the BSE data.
+fn angular_momentum(l: usize) -> Result<AngularMomentum, AtoError> {
+    u8::try_from(l)
+        .ok()
+        .and_then(|l| AngularMomentum::try_from(l).ok())
+        .ok_or_else(|| {
+            AtoError::Parse(format!(
+                "{} is not a valid angular momentum, only numbers between 0 and 10 are supported",
+                l
+            ))
+        })
+}
+
+/// Parse the string representation of exponents or coefficients.
+fn parse_floats(values: &[String]) -> Result<Vec<f64>, AtoError> {
+    values
+        .iter()
+        .map(|x| {
+            x.parse::<f64>()
+                .map_err(|_| AtoError::Parse(format!("{} is not a valid number", x)))
+        })
+        .collect()
 }
 
 impl From<&str> for BasisSet {
+    /// Panics if the basis set can not be loaded, see [BasisSet::try_new].
     fn from(name: &str) -> Self {
-        let data = BasisSetExchange::read_basis(name).unwrap();
-        Self::from(data)
+        Self::try_new(name).unwrap()
     }
 }
 
@@ -69,16 +339,110 @@ impl BasisSet {
     /// Create a basis set.
     ///
     /// The names are identical to the ones listed at
-    /// [BasisSetExchange](https://www.basissetexchange.org). If the library is called the first
-    /// time it will download all basis sets from BSE and requires therefore an internet connection.
-    /// The data of all basis sets have a size of about 400 MB and will be placed at
-    /// $HOME/.ato_rs/data/basis_sets/
+    /// [BasisSetExchange](https://www.basissetexchange.org). If a basis set is requested the first
+    /// time it will be downloaded from BSE and requires therefore an internet connection.
+    /// The data will be placed at $HOME/.ato_rs/data/basis_sets/. All basis sets (about 400 MB)
+    /// can be prefetched with [crate::files::ensure_data_exist].
     /// If you want to change the path of the data directory you can set the following environment
-    /// variable: $ATO_DATA_PATH
+    /// variable: $ATO_DATA_PATH. It may list several directories, which are searched in order,
+    /// see [crate::files::data_roots].
+    /// Basis sets in the BSE JSON format that are placed in the `custom` subdirectory of the
+    /// data directory are found by their names as well, see [crate::formats::BseJson].
+    /// Async services can use [BasisSet::try_new_async] instead.
+    ///
+    /// Panics if the basis set can not be loaded. Use [BasisSet::try_new] to handle the error.
     pub fn new(name: &str) -> Self {
         Self::from(name)
     }
 
+    /// Create a basis set and return an error if it can not be loaded.
+    ///
+    /// This behaves like [BasisSet::new], but an unknown name, a failed download or invalid
+    /// data is reported as [AtoError] instead of a panic.
+    pub fn try_new(name: &str) -> Result<Self, AtoError> {
+        Self::from_source(&BasisSetExchange::from_env()?, name)
+    }
+
+    /// Async variant of [BasisSet::try_new], for use inside of a Tokio runtime.
+    ///
+    /// The blocking constructors work inside of a runtime as well, but block the calling
+    /// thread while the basis set is downloaded.
+    pub async fn try_new_async(name: &str) -> Result<Self, AtoError> {
+        Self::from_exchange_async(&BasisSetExchange::from_env()?, name).await
+    }
+
+    /// Async variant of [BasisSet::from_source] for a configured [BasisSetExchange].
+    pub async fn from_exchange_async(bse: &BasisSetExchange, name: &str) -> Result<Self, AtoError> {
+        let data = bse.load_async(name).await?;
+        let mut basis = Self::try_from(data)?;
+        basis.id = bse.resolve_async(name).await?;
+        Ok(basis)
+    }
+
+    /// Create a basis set from any [BasisSource], e.g. a local directory of BSE JSON files or
+    /// basis sets that are embedded into the binary.
+    pub fn from_source<S: BasisSource + ?Sized>(source: &S, name: &str) -> Result<Self, AtoError> {
+        let data = source.load(name)?;
+        let mut basis = Self::try_from(data)?;
+        basis.id = source.resolve(name)?;
+        Ok(basis)
+    }
+
+    /// Create a specific version of a basis set.
+    ///
+    /// The available versions are listed for every basis set at
+    /// [BasisSetExchange](https://www.basissetexchange.org). An error is returned if the
+    /// version does not exist. Use this to obtain reproducible results, as [BasisSet::new]
+    /// always returns the latest version that was downloaded.
+    pub fn try_with_version(name: &str, version: &str) -> Result<Self, AtoError> {
+        Self::from_source_with_version(&BasisSetExchange::from_env()?, name, version)
+    }
+
+    /// Async variant of [BasisSet::try_with_version], for use inside of a Tokio runtime.
+    pub async fn try_with_version_async(name: &str, version: &str) -> Result<Self, AtoError> {
+        Self::from_exchange_with_version_async(&BasisSetExchange::from_env()?, name, version).await
+    }
+
+    /// Async variant of [BasisSet::from_source_with_version] for a configured
+    /// [BasisSetExchange].
+    pub async fn from_exchange_with_version_async(
+        bse: &BasisSetExchange,
+        name: &str,
+        version: &str,
+    ) -> Result<Self, AtoError> {
+        let data = bse.load_version_async(name, version).await?;
+        let mut basis = Self::try_from(data)?;
+        basis.id = bse.resolve_async(name).await?;
+        Ok(basis)
+    }
+
+    /// Create a specific version of a basis set from any [BasisSource].
+    pub fn from_source_with_version<S: BasisSource + ?Sized>(
+        source: &S,
+        name: &str,
+        version: &str,
+    ) -> Result<Self, AtoError> {
+        let data = source.load_version(name, version)?;
+        let mut basis = Self::try_from(data)?;
+        basis.id = source.resolve(name)?;
+        Ok(basis)
+    }
+
+    /// Serialize the basis set into the compact binary format of bincode.
+    ///
+    /// The string representation of the BSE data is not kept, so that values that are written
+    /// with [crate::formats::BseJson] afterwards are formatted again.
+    #[cfg(feature = "serde")]
+    pub fn to_bincode(&self) -> Result<Vec<u8>, AtoError> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// Deserialize a basis set that was serialized with [BasisSet::to_bincode].
+    #[cfg(feature = "serde")]
+    pub fn from_bincode(bytes: &[u8]) -> Result<Self, AtoError> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+
     /// Create a STO-3G basis set.
     ///
     /// This function serves merely as a convenience feature.
@@ -86,8 +450,174 @@ impl BasisSet {
         Self::from("STO-3G")
     }
 
-    pub fn repr_basis_set(&self, element: Element) -> String {
-        let functions: &[BasisFunction] = self.basis_functions.get(&element).unwrap();
+    /// Use all shells in the given form, regardless of the function types of the BSE data.
+    pub fn set_function_type(&mut self, function_type: FunctionType) {
+        for function in self.basis_functions.values_mut().flatten() {
+            function.function_type = function_type;
+        }
+    }
+
+    /// Like [BasisSet::set_function_type], but consumes and returns the basis set.
+    pub fn with_function_type(mut self, function_type: FunctionType) -> Self {
+        self.set_function_type(function_type);
+        self
+    }
+
+    /// Number of atomic orbitals of an element, including the Slater-type orbitals.
+    pub fn n_functions(&self, element: Element) -> Result<usize, AtoError> {
+        let gaussians = self.basis_functions.get(&element);
+        let slaters = self.slater_functions.get(&element);
+        if gaussians.is_none() && slaters.is_none() {
+            return Err(AtoError::MissingElement {
+                basis: self.name.clone(),
+                element,
+            });
+        }
+        Ok(
+            gaussians.map_or(0, |f| f.iter().map(BasisFunction::n_functions).sum())
+                + slaters.map_or(0, |f| f.iter().map(SlaterFunction::n_functions).sum()),
+        )
+    }
+
+    /// Replace all Slater-type shells by their STO-nG expansions with `n_gaussians` Gaussians.
+    ///
+    /// The expanded shells are appended to the Gaussian basis functions of the elements, see
+    /// [SlaterFunction::to_gaussians].
+    pub fn with_gaussian_expansion(mut self, n_gaussians: usize) -> Result<Self, AtoError> {
+        for (element, slater_functions) in std::mem::take(&mut self.slater_functions) {
+            let functions = self.basis_functions.entry(element).or_default();
+            for slater_function in slater_functions.iter() {
+                functions.push(slater_function.to_gaussians(n_gaussians)?);
+            }
+        }
+        Ok(self)
+    }
+
+    /// The effective core potential of an element, if its core electrons are replaced.
+    pub fn ecp(&self, element: Element) -> Option<&EffectiveCorePotential> {
+        self.ecps.get(&element)
+    }
+
+    /// Number of core electrons of the element that are replaced by an ECP.
+    pub fn core_electrons(&self, element: Element) -> usize {
+        self.ecp(element).map_or(0, |ecp| ecp.core_electrons)
+    }
+
+    /// The auxiliary basis set for a role, e.g. `jkfit` for density-fitted SCF or `rifit` for
+    /// RI-MP2. See [BasisSet::auxiliary_from].
+    pub fn auxiliary(&self, role: &str) -> Result<Self, AtoError> {
+        self.auxiliary_from(&BasisSetExchange::from_env()?, role)
+    }
+
+    /// The auxiliary basis set for a role from a configured [BasisSetExchange].
+    ///
+    /// The auxiliary basis sets are taken from the data of this basis set or, if it lists
+    /// none, from its entry in the [Catalogue]. The first one whose role in the catalogue
+    /// matches is loaded. [AtoError::MissingAuxiliary] is returned if there is none.
+    pub fn auxiliary_from(&self, bse: &BasisSetExchange, role: &str) -> Result<Self, AtoError> {
+        let catalogue = Catalogue::from_exchange(bse)?;
+        let auxiliaries = match catalogue.get(&self.id) {
+            Some(entry) if self.auxiliaries.is_empty() => &entry.auxiliaries,
+            _ => &self.auxiliaries,
+        };
+        let entry = catalogue.companion(&self.name, auxiliaries, role)?;
+        Self::from_source(bse, &entry.id)
+    }
+
+    /// Keys of the literature references of the given elements, without duplicates.
+    ///
+    /// The keys are ordered by the atomic number of the first element that cites them.
+    pub fn reference_keys(&self, elements: &[Element]) -> Result<Vec<String>, AtoError> {
+        let mut elements: Vec<Element> = elements.to_vec();
+        elements.sort();
+        elements.dedup();
+        let mut keys: Vec<String> = Vec::new();
+        for element in elements {
+            if !self.basis_functions.contains_key(&element)
+                && !self.ecps.contains_key(&element)
+                && !self.slater_functions.contains_key(&element)
+            {
+                return Err(AtoError::MissingElement {
+                    basis: self.name.clone(),
+                    element,
+                });
+            }
+            for key in self
+                .references
+                .get(&element)
+                .into_iter()
+                .flatten()
+                .flat_map(|reference| reference.reference_keys.iter())
+            {
+                if !keys.contains(key) {
+                    keys.push(key.clone());
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Citations for the use of the basis set for the given elements, e.g. all atoms of a
+    /// molecule. See [BasisSet::citations_from].
+    pub fn citations(
+        &self,
+        elements: &[Element],
+        format: CitationFormat,
+    ) -> Result<String, AtoError> {
+        self.citations_from(&BasisSetExchange::from_env()?, elements, format)
+    }
+
+    /// Citations for the use of the basis set for the given elements from a configured
+    /// [BasisSetExchange].
+    ///
+    /// The paper of the BSE comes first, followed by the records of the
+    /// [BasisSet::reference_keys]. The records are requested from the references endpoint of
+    /// the BSE once and cached in the data directory. They are downloaded again, if the cache
+    /// lacks one of the keys. Basis sets that do not come from the BSE, e.g. custom or
+    /// generated ones, are an [AtoError::InvalidArgument].
+    pub fn citations_from(
+        &self,
+        bse: &BasisSetExchange,
+        elements: &[Element],
+        format: CitationFormat,
+    ) -> Result<String, AtoError> {
+        let keys = self.reference_keys(elements)?;
+        if !bse.is_bse_basis(&self.id)? {
+            return Err(AtoError::InvalidArgument(format!(
+                "{} is not a basis set of the BSE, its references are unknown",
+                self.name
+            )));
+        }
+        let mut cited: Vec<Reference> = vec![bse_reference()];
+        if !keys.is_empty() {
+            let mut records = bse.references(&self.id)?;
+            if keys.iter().any(|key| !records.contains_key(key)) {
+                update_references(bse, &self.id)
+                    .map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+                records = bse.references(&self.id)?;
+            }
+            for key in keys {
+                let record = records.remove(&key).ok_or_else(|| {
+                    AtoError::Parse(format!("the BSE has no record of the reference {}", key))
+                })?;
+                cited.push(record);
+            }
+        }
+        Ok(references::write(&cited, format))
+    }
+
+    /// Representation of the shells of an element.
+    ///
+    /// Use [crate::formats::gaussian94::write] to write the whole basis set in a format that
+    /// can be read again.
+    pub fn repr_basis_set(&self, element: Element) -> Result<String, AtoError> {
+        let functions: &[BasisFunction] =
+            self.basis_functions
+                .get(&element)
+                .ok_or_else(|| AtoError::MissingElement {
+                    basis: self.name.clone(),
+                    element,
+                })?;
         let mut txt = "".to_owned();
         for function in functions.iter() {
             txt += &format!(
@@ -100,7 +630,7 @@ impl BasisSet {
                 txt += &format!("{:18.14e} {:18.14e}\n", e, c);
             }
         }
-        txt
+        Ok(txt)
     }
 }
 
@@ -108,3 +638,339 @@ impl BasisSet {
 fn test_sto3g() {
     BasisSet::sto3g();
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::angular_momentum::AngularMomentum;
+    use crate::basis_function::FunctionType;
+    use crate::basis_set::BasisSet;
+    use crate::bse::basisset_json::InputData;
+    use crate::bse::source::MemorySource;
+    use crate::elements::Element;
+    use crate::error::AtoError;
+    use crate::formats::{BasisFormat, BseJson, Gaussian94};
+    use serde_json::from_str;
+    use std::convert::TryFrom;
+
+    /// Carbon of 6-31G: one s shell followed by two SP shells.
+    const CARBON_631G: &str = r#"
+    {
+        "molssi_bse_schema": {"schema_type": "complete", "schema_version": "0.1"},
+        "revision_description": "Data from the Original Basis Set Exchange",
+        "revision_date": "2018-06-19",
+        "elements": {
+            "6": {
+                "electron_shells": [
+                    {
+                        "function_type": "gto",
+                        "region": "",
+                        "angular_momentum": [0],
+                        "exponents": ["0.3047524880E+04", "0.4573695180E+03", "0.1039486850E+03",
+                                      "0.2921015530E+02", "0.9286662960E+01", "0.3163926960E+01"],
+                        "coefficients": [["0.1834737132E-02", "0.1403732281E-01", "0.6884262226E-01",
+                                          "0.2321844432E+00", "0.4679413484E+00", "0.3623119853E+00"]]
+                    },
+                    {
+                        "function_type": "gto",
+                        "region": "",
+                        "angular_momentum": [0, 1],
+                        "exponents": ["0.7868272350E+01", "0.1881288540E+01", "0.5442492580E+00"],
+                        "coefficients": [["-0.1193324198E+00", "-0.1608541517E+00", "0.1143456438E+01"],
+                                         ["0.6899906659E-01", "0.3164239610E+00", "0.7443082909E+00"]]
+                    },
+                    {
+                        "function_type": "gto",
+                        "region": "",
+                        "angular_momentum": [0, 1],
+                        "exponents": ["0.1687144782E+00"],
+                        "coefficients": [["0.1000000000E+01"], ["0.1000000000E+01"]]
+                    }
+                ],
+                "references": []
+            }
+        },
+        "version": "1",
+        "function_types": ["gto", "gto_cartesian"],
+        "names": ["6-31G"],
+        "tags": [],
+        "family": "pople",
+        "description": "6-31G valence double-zeta",
+        "role": "orbital",
+        "auxiliaries": {},
+        "name": "6-31G"
+    }"#;
+
+    /// Hydrogen with a general contraction: two columns sharing one set of exponents.
+    const HYDROGEN_GENERAL: &str = r#"
+    {
+        "molssi_bse_schema": {"schema_type": "complete", "schema_version": "0.1"},
+        "revision_description": "",
+        "revision_date": "",
+        "elements": {
+            "1": {
+                "electron_shells": [
+                    {
+                        "function_type": "gto_spherical",
+                        "region": "valence",
+                        "angular_momentum": [0],
+                        "exponents": ["13.01", "1.962", "0.4446", "0.122"],
+                        "coefficients": [["0.0196850", "0.1379770", "0.4781480", "0.5012400"],
+                                         ["0.0", "0.0", "0.0", "1.0"]]
+                    },
+                    {
+                        "function_type": "gto_spherical",
+                        "region": "polarization",
+                        "angular_momentum": [1],
+                        "exponents": ["0.727"],
+                        "coefficients": [["1.0"]]
+                    }
+                ],
+                "references": []
+            }
+        },
+        "version": "1",
+        "function_types": ["gto", "gto_spherical"],
+        "names": ["test"],
+        "tags": [],
+        "family": "test",
+        "description": "general contraction test",
+        "role": "orbital",
+        "auxiliaries": {},
+        "name": "test"
+    }"#;
+
+    /// Iodine of def2-SVP, shortened to one shell and two ECP projectors.
+    const IODINE_DEF2: &str = r#"
+    {
+        "revision_description": "",
+        "revision_date": "2017-04-27",
+        "elements": {
+            "53": {
+                "electron_shells": [
+                    {
+                        "function_type": "gto_spherical",
+                        "region": "",
+                        "angular_momentum": [0],
+                        "exponents": ["5899.5791533", "898.54238765"],
+                        "coefficients": [["0.00024188", "0.0017141"]]
+                    }
+                ],
+                "references": [],
+                "ecp_electrons": 28,
+                "ecp_potentials": [
+                    {
+                        "ecp_type": "scalar_ecp",
+                        "angular_momentum": [2],
+                        "r_exponents": [2],
+                        "gaussian_exponents": ["1.0"],
+                        "coefficients": [["0.0"]]
+                    },
+                    {
+                        "ecp_type": "scalar_ecp",
+                        "angular_momentum": [0],
+                        "r_exponents": [2, 2],
+                        "gaussian_exponents": ["40.0337", "17.3537"],
+                        "coefficients": [["49.9942", "281.0264"]]
+                    }
+                ]
+            }
+        },
+        "version": "1",
+        "function_types": ["gto", "gto_spherical", "scalar_ecp"],
+        "names": ["def2-SVP"],
+        "tags": [],
+        "family": "ahlrichs",
+        "description": "def2-SVP",
+        "role": "orbital",
+        "auxiliaries": {},
+        "name": "def2-SVP"
+    }"#;
+
+    #[test]
+    fn test_ecp() {
+        let data: InputData = from_str(IODINE_DEF2).unwrap();
+        let basis = BasisSet::try_from(data).unwrap();
+        assert_eq!(basis.basis_functions.get(&Element::I).unwrap().len(), 1);
+        assert_eq!(basis.core_electrons(Element::I), 28);
+        assert_eq!(basis.core_electrons(Element::H), 0);
+        let ecp = basis.ecp(Element::I).unwrap();
+        assert_eq!(ecp.projectors.len(), 2);
+        assert_eq!(ecp.max_l(), Some(AngularMomentum::D));
+        assert_eq!(ecp.local().len(), 1);
+        assert_eq!(ecp.semi_local()[0].l, AngularMomentum::S);
+        assert_eq!(ecp.semi_local()[0].r_exponents, vec![2, 2]);
+        assert_eq!(ecp.semi_local()[0].coefficients, vec![49.9942, 281.0264]);
+    }
+
+    #[test]
+    fn test_invalid_ecp() {
+        let json = IODINE_DEF2.replace("[2, 2]", "[2]");
+        let data: InputData = from_str(&json).unwrap();
+        assert!(matches!(BasisSet::try_from(data), Err(AtoError::Parse(_))));
+    }
+
+    /// Helium with a double zeta basis of Slater-type orbitals.
+    const HELIUM_STO: &str = r#"
+    {
+        "molssi_bse_schema": {"schema_type": "complete", "schema_version": "0.1"},
+        "revision_description": "Data from the ADF basis set library",
+        "revision_date": "2020-07-29",
+        "elements": {
+            "2": {
+                "electron_shells": [
+                    {
+                        "function_type": "sto",
+                        "region": "valence",
+                        "angular_momentum": [0],
+                        "r_exponents": [1, 1],
+                        "exponents": ["1.4", "2.6"],
+                        "coefficients": [["1.0", "0.0"], ["0.0", "1.0"]]
+                    },
+                    {
+                        "function_type": "sto",
+                        "region": "polarization",
+                        "angular_momentum": [1],
+                        "r_exponents": [2],
+                        "exponents": ["1.5"],
+                        "coefficients": [["1.0"]]
+                    }
+                ],
+                "references": []
+            }
+        },
+        "version": "1",
+        "function_types": ["sto"],
+        "names": ["DZP"],
+        "tags": [],
+        "family": "adf",
+        "description": "DZP",
+        "role": "orbital",
+        "auxiliaries": {},
+        "name": "DZP"
+    }"#;
+
+    #[test]
+    fn test_slater_functions() {
+        let data: InputData = from_str(HELIUM_STO).unwrap();
+        let basis = BasisSet::try_from(data).unwrap();
+        assert!(basis.basis_functions.is_empty());
+        let functions = &basis.slater_functions[&Element::He];
+        assert_eq!(functions.len(), 3);
+        assert_eq!(functions[2].l, AngularMomentum::P);
+        assert_eq!(functions[2].principal_numbers, vec![2]);
+        assert_eq!(basis.n_functions(Element::He).unwrap(), 5);
+
+        let written: serde_json::Value = from_str(&BseJson.write(&basis).unwrap()).unwrap();
+        assert_eq!(written, from_str::<serde_json::Value>(HELIUM_STO).unwrap());
+        assert!(matches!(
+            Gaussian94.write(&basis),
+            Err(AtoError::UnsupportedFunctionType(_))
+        ));
+
+        let basis = basis.with_gaussian_expansion(3).unwrap();
+        assert!(basis.slater_functions.is_empty());
+        assert_eq!(basis.n_functions(Element::He).unwrap(), 5);
+        assert_eq!(basis.basis_functions[&Element::He][0].exponents.len(), 6);
+        assert!(Gaussian94.write(&basis).is_ok());
+
+        let json = HELIUM_STO.replace("[2]", "[1]");
+        let data: InputData = from_str(&json).unwrap();
+        assert!(matches!(BasisSet::try_from(data), Err(AtoError::Parse(_))));
+    }
+
+    #[test]
+    fn test_all_shells_sp() {
+        let data: InputData = from_str(CARBON_631G).unwrap();
+        let basis = BasisSet::try_from(data).unwrap();
+        let functions = basis.basis_functions.get(&Element::C).unwrap();
+        let momenta: Vec<AngularMomentum> = functions.iter().map(|f| f.l).collect();
+        assert_eq!(
+            momenta,
+            vec![
+                AngularMomentum::S,
+                AngularMomentum::S,
+                AngularMomentum::P,
+                AngularMomentum::S,
+                AngularMomentum::P
+            ]
+        );
+        assert_eq!(functions[0].exponents.len(), 6);
+        assert_eq!(functions[2].exponents, functions[1].exponents);
+        assert_eq!(functions[2].coefficients[2], 0.7443082909);
+    }
+
+    #[test]
+    fn test_general_contraction() {
+        let data: InputData = from_str(HYDROGEN_GENERAL).unwrap();
+        let basis = BasisSet::try_from(data).unwrap();
+        let functions = basis.basis_functions.get(&Element::H).unwrap();
+        assert_eq!(functions.len(), 3);
+        assert_eq!(functions[0].l, AngularMomentum::S);
+        assert_eq!(functions[1].l, AngularMomentum::S);
+        assert_eq!(functions[1].coefficients, vec![0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(functions[2].l, AngularMomentum::P);
+    }
+
+    #[test]
+    fn test_function_types() {
+        // All shells of 6-31G are plain `gto`, but the basis set is declared as Cartesian.
+        let data: InputData = from_str(CARBON_631G).unwrap();
+        let basis = BasisSet::try_from(data).unwrap();
+        assert!(basis.basis_functions[&Element::C]
+            .iter()
+            .all(|f| f.function_type == FunctionType::Cartesian));
+
+        // Hydrogen with two s shells and one spherical d shell.
+        let data: InputData = from_str(&HYDROGEN_GENERAL.replace("[1]", "[2]")).unwrap();
+        let basis = BasisSet::try_from(data).unwrap();
+        assert_eq!(basis.n_functions(Element::H).unwrap(), 7);
+        let basis = basis.with_function_type(FunctionType::Cartesian);
+        assert_eq!(basis.n_functions(Element::H).unwrap(), 8);
+        assert!(basis.n_functions(Element::C).is_err());
+    }
+
+    #[test]
+    fn test_invalid_exponent() {
+        let json = HYDROGEN_GENERAL.replace("\"0.727\"", "\"0.7.27\"");
+        let data: InputData = from_str(&json).unwrap();
+        assert!(matches!(BasisSet::try_from(data), Err(AtoError::Parse(_))));
+    }
+
+    #[test]
+    fn test_invalid_angular_momentum() {
+        let json = HYDROGEN_GENERAL.replace("[1]", "[11]");
+        let data: InputData = from_str(&json).unwrap();
+        assert!(matches!(BasisSet::try_from(data), Err(AtoError::Parse(_))));
+    }
+
+    #[test]
+    fn test_invalid_atomic_number() {
+        for number in ["300", "0"] {
+            let json = CARBON_631G.replace("\"6\": {", &format!("\"{}\": {{", number));
+            let data: InputData = from_str(&json).unwrap();
+            assert!(matches!(BasisSet::try_from(data), Err(AtoError::Parse(_))));
+        }
+    }
+
+    #[test]
+    fn test_from_source() {
+        let mut source = MemorySource::new();
+        source.insert_json(CARBON_631G).unwrap();
+        let basis = BasisSet::from_source(&source, "6-31g").unwrap();
+        assert_eq!(basis.name, "6-31G".to_string());
+        assert_eq!(basis.version, "1".to_string());
+        assert_eq!(basis.revision_date, "2018-06-19".to_string());
+        assert_eq!(basis.basis_functions.get(&Element::C).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_missing_element() {
+        let data: InputData = from_str(HYDROGEN_GENERAL).unwrap();
+        let basis = BasisSet::try_from(data).unwrap();
+        assert!(basis.repr_basis_set(Element::H).is_ok());
+        assert!(matches!(
+            basis.repr_basis_set(Element::C),
+            Err(AtoError::MissingElement { .. })
+        ));
+    }
+}
@@ -1,10 +1,12 @@
-use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InputData {
+    #[serde(default)]
+    pub molssi_bse_schema: InputSchema,
     pub name: String,
-    pub elements: HashMap<usize, InputElement>,
+    pub elements: BTreeMap<usize, InputElement>,
     pub description: String,
     pub revision_description: String,
     pub revision_date: String,
@@ -14,32 +16,86 @@ pub struct InputData {
     pub tags: Vec<String>,
     pub family: String,
     pub role: String,
+    /// Auxiliary basis sets by their role, e.g. `jkfit`.
+    #[serde(default)]
+    pub auxiliaries: BTreeMap<String, InputAuxiliary>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputSchema {
+    pub schema_type: String,
+    pub schema_version: String,
+}
+
+impl Default for InputSchema {
+    fn default() -> Self {
+        Self {
+            schema_type: "complete".to_owned(),
+            schema_version: "0.1".to_owned(),
+        }
+    }
+}
+
+/// The BSE lists either one or several auxiliary basis sets per role.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum InputAuxiliary {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl InputAuxiliary {
+    pub fn names(&self) -> Vec<String> {
+        match self {
+            InputAuxiliary::One(name) => vec![name.clone()],
+            InputAuxiliary::Many(names) => names.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InputElement {
+    /// Elements of pure ECP basis sets (e.g. def2-ECP) have no electron shells.
+    #[serde(default)]
     pub electron_shells: Vec<InputShell>,
     pub references: Vec<InputReferences>,
+    /// Number of core electrons that are replaced by the ECP.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ecp_electrons: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ecp_potentials: Vec<InputPotential>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InputReferences {
     pub reference_description: String,
     pub reference_keys: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InputShell {
     pub angular_momentum: Vec<usize>,
     pub exponents: Vec<String>,
     pub coefficients: Vec<Vec<String>>,
     pub function_type: String,
     pub region: String,
+    /// Principal quantum numbers of the primitives of Slater-type (`sto`) shells.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub r_exponents: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InputPotential {
+    pub angular_momentum: Vec<usize>,
+    pub r_exponents: Vec<i32>,
+    pub gaussian_exponents: Vec<String>,
+    pub coefficients: Vec<Vec<String>>,
+    pub ecp_type: String,
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::bse::basisset_json::{InputData, InputElement, InputShell};
+    use crate::bse::basisset_json::{InputData, InputElement, InputPotential, InputShell};
     use serde_json::from_str;
 
     #[test]
@@ -83,6 +139,8 @@ mod tests {
         assert_eq!(data.role, "orbital".to_string());
         assert_eq!(data.name, "STO-2G".to_string());
         assert_eq!(data.function_types, vec!["gto"]);
+        assert_eq!(data.molssi_bse_schema.schema_type, "complete".to_string());
+        assert!(data.auxiliaries.is_empty());
     }
 
     #[test]
@@ -158,4 +216,29 @@ mod tests {
         assert_eq!(data.function_type, "gto".to_string());
         assert_eq!(data.region, "".to_string());
     }
+
+    #[test]
+    fn test_potential() {
+        let element_string = r#"
+        {
+            "references": [],
+            "ecp_electrons": 28,
+            "ecp_potentials": [
+                {
+                    "ecp_type": "scalar_ecp",
+                    "angular_momentum": [0],
+                    "r_exponents": [2, 2],
+                    "gaussian_exponents": ["3.5", "1.7"],
+                    "coefficients": [["49.9", "20.2"]]
+                }
+            ]
+        }"#;
+        let data: InputElement = from_str(element_string).unwrap();
+        assert!(data.electron_shells.is_empty());
+        assert_eq!(data.ecp_electrons, Some(28));
+        let potential: &InputPotential = &data.ecp_potentials[0];
+        assert_eq!(potential.ecp_type, "scalar_ecp".to_string());
+        assert_eq!(potential.r_exponents, vec![2, 2]);
+        assert_eq!(potential.coefficients[0].len(), 2);
+    }
 }
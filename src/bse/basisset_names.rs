@@ -1 +1,180 @@
+use crate::error::AtoError;
+use std::collections::BTreeMap;
 
+/// Maximum number of suggestions that are returned for an unknown name.
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// Normalize the name of a basis set for comparisons.
+///
+/// The name is lowercased and separators like `-`, `_`, `.`, `,` and whitespace are removed.
+/// The Pople notations `(d)` and `(d,p)` are replaced by `*` and `**`, so that `6-31G(d)`
+/// and `6-31g*` are considered to be the same name.
+///
+/// ```
+/// use ato::bse::basisset_names::normalize;
+/// assert_eq!(normalize("6-31G(d,p)"), normalize("6-31g**"));
+/// assert_eq!(normalize("Def2 SVP"), normalize("def2-svp"));
+/// assert_ne!(normalize("def2-SV(P)"), normalize("def2-SVP"));
+/// ```
+pub fn normalize(name: &str) -> String {
+    name.to_lowercase()
+        .replace(' ', "")
+        .replace("(d,p)", "**")
+        .replace("(d)", "*")
+        .chars()
+        .filter(|c| !matches!(c, '-' | '_' | '.' | ',') && !c.is_whitespace())
+        .collect()
+}
+
+/// Levenshtein distance between two strings.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// Index of all names and aliases of a set of basis sets.
+///
+/// Lookups are done on the [normalize]d names. If a name is unknown, the closest names are
+/// suggested.
+#[derive(Debug, Clone, Default)]
+pub struct NameIndex {
+    /// Normalized alias => (identifier, alias as it was given).
+    aliases: BTreeMap<String, (String, String)>,
+}
+
+impl NameIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a name of the basis set with the identifier `id`. Aliases that are already known
+    /// are not overwritten.
+    pub fn insert(&mut self, id: &str, alias: &str) {
+        self.aliases
+            .entry(normalize(alias))
+            .or_insert_with(|| (id.to_owned(), alias.to_owned()));
+    }
+
+    /// Identifier of the basis set with the given name or alias.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.aliases
+            .get(&normalize(name))
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Identifier of the basis set with the given name, or an error that contains suggestions.
+    pub fn resolve(&self, name: &str) -> Result<String, AtoError> {
+        self.get(name)
+            .map(str::to_owned)
+            .ok_or_else(|| AtoError::UnknownBasis {
+                name: name.to_owned(),
+                suggestions: self.suggestions(name),
+            })
+    }
+
+    /// Names that are similar to the given one, ordered from the most similar one.
+    ///
+    /// Only one name is suggested per basis set.
+    pub fn suggestions(&self, name: &str) -> Vec<String> {
+        let normalized = normalize(name);
+        let limit = (normalized.chars().count() / 3).max(2);
+        let mut candidates: Vec<(usize, &str, &str)> = self
+            .aliases
+            .iter()
+            .map(|(alias, (id, original))| {
+                // Names that start with the input, e.g. "cc-pvdz" for "cc-pv", are close matches.
+                let dist = if alias.starts_with(&normalized) && !normalized.is_empty() {
+                    1
+                } else {
+                    distance(&normalized, alias)
+                };
+                (dist, id.as_str(), original.as_str())
+            })
+            .filter(|(dist, _, _)| *dist <= limit)
+            .collect();
+        candidates.sort();
+
+        let mut ids: Vec<&str> = Vec::new();
+        let mut suggestions: Vec<String> = Vec::new();
+        for (_, id, original) in candidates {
+            if ids.contains(&id) {
+                continue;
+            }
+            ids.push(id);
+            suggestions.push(original.to_owned());
+            if suggestions.len() == MAX_SUGGESTIONS {
+                break;
+            }
+        }
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bse::basisset_names::{distance, NameIndex};
+    use crate::error::AtoError;
+
+    fn index() -> NameIndex {
+        let mut index = NameIndex::new();
+        for (id, names) in [
+            ("6-31g_st_", vec!["6-31G*", "6-31G(d)"]),
+            ("6-31g_st__st_", vec!["6-31G**", "6-31G(d,p)"]),
+            ("6-31g", vec!["6-31G"]),
+            ("def2-svp", vec!["def2-SVP", "Def2-SVP"]),
+            ("def2-sv_p_", vec!["def2-SV(P)"]),
+            ("cc-pvdz", vec!["cc-pVDZ"]),
+        ] {
+            for name in names {
+                index.insert(id, name);
+            }
+        }
+        index
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("svp", "svp"), 0);
+    }
+
+    #[test]
+    fn test_resolve_aliases() {
+        let index = index();
+        assert_eq!(index.resolve("6-31g*").unwrap(), "6-31g_st_");
+        assert_eq!(index.resolve("6-31G(d)").unwrap(), "6-31g_st_");
+        assert_eq!(index.resolve("6-31G(D,P)").unwrap(), "6-31g_st__st_");
+        assert_eq!(index.resolve("def2svp").unwrap(), "def2-svp");
+        assert_eq!(index.resolve("DEF2 SV(P)").unwrap(), "def2-sv_p_");
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let index = index();
+        match index.resolve("cc-pvdzz") {
+            Err(AtoError::UnknownBasis { suggestions, .. }) => {
+                assert_eq!(suggestions, vec!["cc-pVDZ"])
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        let suggestions = index.suggestions("6-31g+");
+        assert_eq!(suggestions[0], "6-31G");
+        assert!(suggestions.contains(&"6-31G*".to_string()));
+        assert!(index.suggestions("completely-unknown").is_empty());
+    }
+}
@@ -0,0 +1,402 @@
+use crate::bse::basisset_json::{
+    InputAuxiliary, InputData, InputElement, InputPotential, InputReferences, InputSchema,
+    InputShell,
+};
+use crate::bse::source::BasisSource;
+use crate::catalogue::{Catalogue, CatalogueEntry};
+use crate::error::AtoError;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+/// Name of the metadata file in the data directory of the BSE repository.
+pub const CHECKOUT_METADATA: &str = "METADATA.json";
+
+/// A local checkout of the data of the `basis_set_exchange` repository.
+///
+/// The data is stored in layers: the table file of a basis set version, which is listed as
+/// `file_relpath` in the metadata, refers to an element file for every element, which in turn
+/// lists the component files with the actual shells and potentials. The names, family and
+/// role are stored once for all versions in the metadata file next to the table files. The
+/// layers are combined into the same data as it is returned by the BSE API, so that no HTTP
+/// access is needed:
+///
+/// ```no_run
+/// use ato::basis_set::BasisSet;
+/// use ato::bse::checkout::CheckoutSource;
+/// let source = CheckoutSource::new("basis_set_exchange/basis_set_exchange/data").unwrap();
+/// let basis = BasisSet::from_source(&source, "cc-pVDZ").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CheckoutSource {
+    path: PathBuf,
+    catalogue: Catalogue,
+}
+
+/// The table file of a basis set version, `<base>.<version>.table.json`, with the element
+/// files by atomic number.
+#[derive(Deserialize)]
+struct InputTable {
+    #[serde(default)]
+    revision_description: String,
+    #[serde(default)]
+    revision_date: String,
+    elements: BTreeMap<String, String>,
+}
+
+/// The metadata file `<base>.metadata.json` next to the table files, which is shared by all
+/// versions of a basis set.
+#[derive(Deserialize)]
+struct InputMetadataFile {
+    #[serde(default)]
+    names: Vec<String>,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    function_types: Vec<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    family: String,
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    auxiliaries: BTreeMap<String, InputAuxiliary>,
+}
+
+/// An element file, with the component files by atomic number.
+#[derive(Deserialize)]
+struct InputElementFile {
+    elements: BTreeMap<String, InputComponents>,
+}
+
+#[derive(Deserialize)]
+struct InputComponents {
+    components: Vec<String>,
+}
+
+/// A component file, which contains the data of one publication.
+#[derive(Deserialize)]
+struct InputComponent {
+    #[serde(default)]
+    description: String,
+    elements: BTreeMap<String, InputComponentElement>,
+}
+
+#[derive(Deserialize)]
+struct InputComponentElement {
+    #[serde(default)]
+    electron_shells: Vec<InputShell>,
+    #[serde(default)]
+    references: Vec<String>,
+    ecp_electrons: Option<usize>,
+    #[serde(default)]
+    ecp_potentials: Vec<InputPotential>,
+}
+
+impl CheckoutSource {
+    /// Open the data directory of a checkout, which contains the [CHECKOUT_METADATA] file.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Result<Self, AtoError> {
+        let path = path.into();
+        let catalogue = Catalogue::from_json(&fs::read_to_string(path.join(CHECKOUT_METADATA))?)?;
+        Ok(Self { path, catalogue })
+    }
+
+    /// Catalogue of all basis sets of the checkout.
+    pub fn catalogue(&self) -> &Catalogue {
+        &self.catalogue
+    }
+
+    fn read<T: DeserializeOwned>(&self, relpath: &str) -> Result<T, AtoError> {
+        let data = fs::read_to_string(self.path.join(relpath))?;
+        serde_json::from_str(&data)
+            .map_err(|err| AtoError::Parse(format!("{} is invalid: {}", relpath, err)))
+    }
+
+    /// Combine the table, element and component files of a version of a basis set.
+    fn compose(&self, entry: &CatalogueEntry, version: &str) -> Result<InputData, AtoError> {
+        let table_path = &entry
+            .versions
+            .get(version)
+            .ok_or_else(|| AtoError::UnknownVersion {
+                basis: entry.name.clone(),
+                version: version.to_owned(),
+            })?
+            .file_relpath;
+        let (base, file_version) = table_path
+            .strip_suffix(".table.json")
+            .and_then(|stem| stem.rsplit_once('.'))
+            .ok_or_else(|| {
+                AtoError::Parse(format!("{} is not the path of a table file", table_path))
+            })?;
+        let table: InputTable = self.read(table_path)?;
+        let metadata: InputMetadataFile = self.read(&format!("{}.metadata.json", base))?;
+
+        // Element and component files are shared by many elements.
+        let mut element_files: HashMap<String, InputElementFile> = HashMap::new();
+        let mut components: HashMap<String, InputComponent> = HashMap::new();
+        let mut elements: BTreeMap<usize, InputElement> = BTreeMap::new();
+        for (number, element_path) in table.elements.iter() {
+            let z: usize = number
+                .parse()
+                .map_err(|_| AtoError::Parse(format!("{} is not an atomic number", number)))?;
+            if !element_files.contains_key(element_path) {
+                element_files.insert(element_path.clone(), self.read(element_path)?);
+            }
+            let missing = |path: &str| {
+                AtoError::Parse(format!("{} does not contain the element {}", path, number))
+            };
+            let component_paths = &element_files[element_path]
+                .elements
+                .get(number)
+                .ok_or_else(|| missing(element_path))?
+                .components;
+
+            let mut element = InputElement {
+                electron_shells: Vec::new(),
+                references: Vec::new(),
+                ecp_electrons: None,
+                ecp_potentials: Vec::new(),
+            };
+            for component_path in component_paths.iter() {
+                if !components.contains_key(component_path) {
+                    components.insert(component_path.clone(), self.read(component_path)?);
+                }
+                let component = &components[component_path];
+                let data = component
+                    .elements
+                    .get(number)
+                    .ok_or_else(|| missing(component_path))?;
+                element
+                    .electron_shells
+                    .extend(data.electron_shells.iter().cloned());
+                element.references.push(InputReferences {
+                    reference_description: component.description.clone(),
+                    reference_keys: data.references.clone(),
+                });
+                if data.ecp_electrons.is_some() {
+                    element.ecp_electrons = data.ecp_electrons;
+                }
+                element
+                    .ecp_potentials
+                    .extend(data.ecp_potentials.iter().cloned());
+            }
+            elements.insert(z, element);
+        }
+
+        let name = if !entry.display_name.is_empty() {
+            entry.display_name.clone()
+        } else if let Some(name) = metadata.names.first() {
+            name.clone()
+        } else {
+            base.rsplit('/').next().unwrap_or(base).to_owned()
+        };
+        // The function types are only listed in the metadata of the whole checkout.
+        let function_types = if metadata.function_types.is_empty() {
+            entry.function_types.clone()
+        } else {
+            metadata.function_types
+        };
+        Ok(InputData {
+            molssi_bse_schema: InputSchema::default(),
+            name,
+            elements,
+            description: metadata.description,
+            revision_description: table.revision_description,
+            revision_date: table.revision_date,
+            version: file_version.to_owned(),
+            function_types,
+            names: metadata.names,
+            tags: metadata.tags,
+            family: metadata.family,
+            role: metadata.role,
+            auxiliaries: metadata.auxiliaries,
+        })
+    }
+}
+
+impl BasisSource for CheckoutSource {
+    fn names(&self) -> Result<Vec<String>, AtoError> {
+        Ok(self
+            .catalogue
+            .entries()
+            .map(|entry| entry.name.clone())
+            .collect())
+    }
+
+    fn resolve(&self, name: &str) -> Result<String, AtoError> {
+        Ok(self.catalogue.resolve(name)?.id.clone())
+    }
+
+    fn load(&self, name: &str) -> Result<InputData, AtoError> {
+        let entry = self.catalogue.resolve(name)?;
+        self.compose(entry, &entry.latest_version)
+    }
+
+    fn load_version(&self, name: &str, version: &str) -> Result<InputData, AtoError> {
+        let entry = self.catalogue.resolve(name)?;
+        self.compose(entry, version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::basis_set::BasisSet;
+    use crate::bse::checkout::CheckoutSource;
+    use crate::bse::source::BasisSource;
+    use crate::elements::Element;
+    use crate::error::AtoError;
+    use std::fs;
+
+    const METADATA: &str = r#"
+    {
+        "6-31g": {
+            "auxiliaries": {}, "basename": "6-31G", "description": "6-31G valence double-zeta",
+            "display_name": "6-31G", "family": "pople", "function_types": ["gto"],
+            "latest_version": "1", "notes_exist": [false, false], "other_names": ["631g"],
+            "relpath": "", "role": "orbital", "tags": [],
+            "versions": {
+                "0": {"elements": ["1"], "file_relpath": "6-31G.0.table.json",
+                      "revdate": "2018-06-19", "revdesc": "Data from the EMSL"},
+                "1": {"elements": ["1", "2"], "file_relpath": "6-31G.1.table.json",
+                      "revdate": "2019-09-18", "revdesc": "Add helium"}
+            }
+        }
+    }"#;
+
+    /// Table files only contain the revision and the element files, as in the repository.
+    fn table(revision_description: &str, revision_date: &str, elements: &str) -> String {
+        format!(
+            r#"{{
+                "molssi_bse_schema": {{"schema_type": "table", "schema_version": "0.1"}},
+                "revision_description": "{}",
+                "revision_date": "{}",
+                "elements": {{{}}}
+            }}"#,
+            revision_description, revision_date, elements
+        )
+    }
+
+    const BASIS_METADATA: &str = r#"
+    {
+        "molssi_bse_schema": {"schema_type": "metadata", "schema_version": "0.1"},
+        "names": ["6-31G"],
+        "tags": [],
+        "family": "pople",
+        "description": "6-31G valence double-zeta",
+        "role": "orbital",
+        "auxiliaries": {}
+    }"#;
+
+    const ELEMENT: &str = r#"
+    {
+        "molssi_bse_schema": {"schema_type": "element", "schema_version": "0.1"},
+        "name": "6-31G",
+        "description": "6-31G valence double-zeta",
+        "elements": {
+            "1": {"components": ["pople/6-31G.0.json"]},
+            "2": {"components": ["pople/6-31G.0.json", "pople/6-31G-he.0.json"]}
+        }
+    }"#;
+
+    fn component(description: &str, reference: &str, exponents: &[(&str, &str)]) -> String {
+        let elements: Vec<String> = exponents
+            .iter()
+            .map(|(z, exponent)| {
+                format!(
+                    r#""{}": {{
+                        "electron_shells": [{{
+                            "function_type": "gto", "region": "", "angular_momentum": [0],
+                            "exponents": ["{}"], "coefficients": [["1.0"]]
+                        }}],
+                        "references": ["{}"]
+                    }}"#,
+                    z, exponent, reference
+                )
+            })
+            .collect();
+        format!(
+            r#"{{
+                "molssi_bse_schema": {{"schema_type": "component", "schema_version": "0.1"}},
+                "description": "{}", "data_source": "", "elements": {{{}}}
+            }}"#,
+            description,
+            elements.join(",")
+        )
+    }
+
+    #[test]
+    fn test_checkout_source() {
+        let dir = std::env::temp_dir().join(format!("ato_checkout_source_{}", std::process::id()));
+        fs::create_dir_all(dir.join("pople")).unwrap();
+        fs::write(dir.join("METADATA.json"), METADATA).unwrap();
+        fs::write(dir.join("6-31G.metadata.json"), BASIS_METADATA).unwrap();
+        fs::write(
+            dir.join("6-31G.0.table.json"),
+            table(
+                "Data from the EMSL",
+                "2018-06-19",
+                r#""1": "pople/6-31G.0.element.json""#,
+            ),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("6-31G.1.table.json"),
+            table(
+                "Add helium",
+                "2019-09-18",
+                r#""1": "pople/6-31G.0.element.json", "2": "pople/6-31G.0.element.json""#,
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("pople/6-31G.0.element.json"), ELEMENT).unwrap();
+        fs::write(
+            dir.join("pople/6-31G.0.json"),
+            component(
+                "6-31G",
+                "ditchfield1971a",
+                &[("1", "18.73"), ("2", "38.42")],
+            ),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("pople/6-31G-he.0.json"),
+            component("Helium", "hehre1972a", &[("2", "1.2")]),
+        )
+        .unwrap();
+
+        let source = CheckoutSource::new(&dir).unwrap();
+        assert_eq!(source.names().unwrap(), vec!["6-31G".to_string()]);
+        assert_eq!(source.resolve("631G").unwrap(), "6-31g");
+
+        let data = source.load("6-31g").unwrap();
+        assert_eq!(data.version, "1");
+        assert_eq!(data.name, "6-31G");
+        assert_eq!(data.revision_description, "Add helium");
+        assert_eq!(data.description, "6-31G valence double-zeta");
+        assert_eq!(data.family, "pople");
+        assert_eq!(data.function_types, vec!["gto"]);
+        let helium = &data.elements[&2];
+        assert_eq!(helium.electron_shells.len(), 2);
+        assert_eq!(helium.references[1].reference_description, "Helium");
+        assert_eq!(helium.references[1].reference_keys, vec!["hehre1972a"]);
+
+        let basis = BasisSet::from_source(&source, "6-31G").unwrap();
+        assert_eq!(basis.id, "6-31g");
+        assert_eq!(basis.basis_functions[&Element::He].len(), 2);
+        let old = BasisSet::from_source_with_version(&source, "6-31G", "0").unwrap();
+        assert!(!old.basis_functions.contains_key(&Element::He));
+        assert_eq!(old.version, "0");
+        assert_eq!(old.revision_date, "2018-06-19");
+        assert!(matches!(
+            source.load_version("6-31G", "2"),
+            Err(AtoError::UnknownVersion { .. })
+        ));
+
+        fs::remove_file(dir.join("pople/6-31G-he.0.json")).unwrap();
+        assert!(matches!(source.load("6-31G"), Err(AtoError::Io(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
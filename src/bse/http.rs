@@ -1,100 +1,652 @@
 use crate::bse::basisset_json::InputData;
+use crate::bse::basisset_names::NameIndex;
 use crate::bse::metadata_json::InputMetaData;
+use crate::bse::references_json::{records, InputReferenceSet};
+use crate::bse::source::{BasisSource, DirectorySource};
+use crate::catalogue::Catalogue;
+use crate::error::AtoError;
 use crate::files::{
-    data_path, ensure_data_exist, BASIS_SUBDIRECTORY, BINCODE_BASIS_DICT, JSON_BASIS_DICT,
+    basis_path, basis_root, data_path, data_roots, ensure_basis_exist, ensure_basis_exist_async,
+    ensure_basis_version_exist, ensure_basis_version_exist_async, ensure_metadata_exist,
+    ensure_metadata_exist_async, ensure_references_exist, find_data_path, is_complete_in,
+    latest_entry, references_path, references_suffix, versioned_id, write_atomic, BasisSetChange,
+    DownloadSummary, BASIS_SUBDIRECTORY, BINCODE_BASIS_DICT, CUSTOM_SUBDIRECTORY, JSON_BASIS_DICT,
+    JSON_METADATA,
 };
-use anyhow::{Context, Result};
-use bincode::serialize_into;
+use crate::manifest::Manifest;
+use crate::references::Reference;
+use anyhow::{bail, Context, Result};
 use futures::stream::StreamExt;
-use reqwest::Client;
-use std::collections::BTreeMap;
-use std::fs;
-use std::fs::File;
-use std::io::BufWriter;
+use reqwest::{Client, Proxy, StatusCode};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::{env, fs};
 
 pub const BSE_BASE_URL: &str = "https://www.basissetexchange.org/";
 pub const BSE_BASIS_SUFFIX: &str = "/format/json/?";
 pub const BSE_BASIS: &str = "/api/basis/";
 pub const BSE_METADATA: &str = "/api/metadata/";
+pub const BSE_REFERENCES: &str = "/api/references/";
+pub const BSE_REFERENCES_SUFFIX: &str = "/format/json/";
+pub const BSE_URL_ENV_VAR: &str = "ATO_BSE_URL";
+pub const DEFAULT_CONCURRENCY: usize = 30;
+pub const DEFAULT_RETRIES: u32 = 3;
+pub const DEFAULT_BACKOFF: Duration = Duration::from_millis(500);
+pub const DEFAULT_USER_AGENT: &str = concat!("ato/", env!("CARGO_PKG_VERSION"));
 
-pub struct BasisSetExchange {}
+/// Client for the REST API of the Basis Set Exchange.
+///
+/// The default endpoint is [BSE_BASE_URL], which can be overridden with the environment variable
+/// $ATO_BSE_URL, e.g. to use a local mirror. All other settings are available through the
+/// [BasisSetExchangeBuilder]:
+///
+/// ```
+/// use ato::bse::http::BasisSetExchange;
+/// use std::time::Duration;
+/// let bse = BasisSetExchange::builder()
+///     .base_url("http://localhost:8080")
+///     .timeout(Duration::from_secs(10))
+///     .concurrency(4)
+///     .build()
+///     .unwrap();
+/// assert_eq!(bse.base_url(), "http://localhost:8080");
+/// ```
+#[derive(Debug, Clone)]
+pub struct BasisSetExchange {
+    base_url: String,
+    concurrency: usize,
+    retries: u32,
+    backoff: Duration,
+    client: Client,
+}
+
+/// Builder for a [BasisSetExchange] with a custom endpoint or HTTP settings.
+#[derive(Debug, Clone)]
+pub struct BasisSetExchangeBuilder {
+    base_url: String,
+    timeout: Option<Duration>,
+    concurrency: usize,
+    retries: u32,
+    backoff: Duration,
+    proxy: Option<String>,
+    user_agent: String,
+}
+
+impl Default for BasisSetExchangeBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: env::var(BSE_URL_ENV_VAR).unwrap_or_else(|_| BSE_BASE_URL.to_owned()),
+            timeout: None,
+            concurrency: DEFAULT_CONCURRENCY,
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+            proxy: None,
+            user_agent: DEFAULT_USER_AGENT.to_owned(),
+        }
+    }
+}
+
+impl BasisSetExchangeBuilder {
+    /// Base URL of the BSE instance, e.g. an internal mirror.
+    pub fn base_url(mut self, url: &str) -> Self {
+        self.base_url = url.to_owned();
+        self
+    }
+
+    /// Timeout for each HTTP request. By default no timeout is set.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Number of basis sets that are downloaded at the same time.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Number of times a failed request is repeated, if the error might be transient.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Waiting time before the first retry. It is doubled for every further retry.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Proxy that is used for all requests.
+    pub fn proxy(mut self, url: &str) -> Self {
+        self.proxy = Some(url.to_owned());
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_owned();
+        self
+    }
+
+    pub fn build(self) -> Result<BasisSetExchange, AtoError> {
+        let mut client = Client::builder().user_agent(self.user_agent);
+        if let Some(timeout) = self.timeout {
+            client = client.timeout(timeout);
+        }
+        if let Some(proxy) = self.proxy {
+            client = client.proxy(Proxy::all(proxy)?);
+        }
+        Ok(BasisSetExchange {
+            base_url: self.base_url.trim_end_matches('/').to_owned(),
+            concurrency: self.concurrency,
+            retries: self.retries,
+            backoff: self.backoff,
+            client: client.build()?,
+        })
+    }
+}
 
 impl BasisSetExchange {
-    async fn request_names() -> Result<BTreeMap<String, String>> {
-        let resp = reqwest::get(format!("{}{}", BSE_BASE_URL, BSE_METADATA))
-            .await?
-            .json::<BTreeMap<String, InputMetaData>>()
+    pub fn builder() -> BasisSetExchangeBuilder {
+        BasisSetExchangeBuilder::default()
+    }
+
+    /// Client with the default settings and the endpoint from $ATO_BSE_URL, if it is set.
+    pub fn from_env() -> Result<Self, AtoError> {
+        Self::builder().build()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    async fn request_metadata(&self) -> Result<BTreeMap<String, InputMetaData>> {
+        let bytes = self
+            .fetch(&format!("{}{}", self.base_url, BSE_METADATA))
             .await?;
-        Ok(resp
-            .keys()
-            .zip(resp.values())
-            .map(|(k, v)| (v.basename.to_lowercase(), k.clone()))
-            .collect::<BTreeMap<String, String>>())
-    }
-
-    async fn basis_set_urls() -> Result<Vec<(String, String)>> {
-        let names = BasisSetExchange::request_names().await?;
-        let path = data_path(BASIS_SUBDIRECTORY)?;
-        Ok(names
-            .values()
-            .map(|name| {
-                (
-                    format!("{}{}{}{}", BSE_BASE_URL, BSE_BASIS, &name, BSE_BASIS_SUFFIX),
-                    format!("{}{}.json", &path.to_str().unwrap(), name),
-                )
-            })
-            .collect::<Vec<(String, String)>>())
-    }
-
-    pub async fn download_basis_sets() -> Result<()> {
-        let paths: Vec<(String, String)> = BasisSetExchange::basis_set_urls().await?;
-        let client = Client::builder().build()?;
-        let fetches = futures::stream::iter(paths.into_iter().map(|(url, path)| {
-            let send_fut = client.get(&url).send();
-            async move {
-                match send_fut.await {
-                    Ok(resp) => match resp.bytes().await {
-                        Ok(bytes) => {
-                            fs::write(&path, bytes).unwrap();
-                        }
-                        Err(_) => println!("ERROR reading {}", url),
-                    },
-                    Err(_) => println!("ERROR downloading {}", url),
+        serde_json::from_slice(&bytes).context("Could not deserialize the metadata")
+    }
+
+    fn basis_set_url(&self, id: &str, version: Option<&str>) -> String {
+        let url = format!("{}{}{}{}", self.base_url, BSE_BASIS, id, BSE_BASIS_SUFFIX);
+        match version {
+            Some(version) => format!("{}version={}", url, version),
+            None => url,
+        }
+    }
+
+    /// Request a URL and retry with an exponential backoff, if the error might be transient.
+    async fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        let mut attempt: u32 = 0;
+        loop {
+            let result = async {
+                self.client
+                    .get(url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .bytes()
+                    .await
+            }
+            .await;
+            match result {
+                Ok(bytes) => return Ok(bytes.to_vec()),
+                Err(err) if attempt < self.retries && is_transient(&err) => {
+                    tokio::time::sleep(self.backoff * 2u32.pow(attempt)).await;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err).with_context(|| format!("Could not download {}", url)),
+            }
+        }
+    }
+
+    /// Download a basis set file and return its size and version. The manifest is not updated.
+    ///
+    /// The latest version is stored at the id of the basis set, other versions at their
+    /// [versioned_id].
+    async fn fetch_basis_set(&self, id: &str, version: Option<&str>) -> Result<(u64, String)> {
+        let bytes = self.fetch(&self.basis_set_url(id, version)).await?;
+        let data: InputVersion =
+            serde_json::from_slice(&bytes).context("The basis set file is invalid")?;
+        let path = match version {
+            Some(version) => {
+                if data.version != version {
+                    bail!(
+                        "Requested version {} of {}, but received version {}",
+                        version,
+                        id,
+                        data.version
+                    );
+                }
+                basis_path(&versioned_id(id, version))?
+            }
+            None => basis_path(id)?,
+        };
+        write_atomic(path, &bytes).context("Could not write the basis set file")?;
+        Ok((bytes.len() as u64, data.version))
+    }
+
+    /// Download the given basis sets and record the successful ones in the manifest.
+    ///
+    /// Basis sets that were present before with another version are reported as changes,
+    /// together with the revision description from the metadata.
+    async fn download_ids(
+        &self,
+        ids: Vec<String>,
+        metadata: &BTreeMap<String, InputMetaData>,
+    ) -> Result<DownloadSummary> {
+        // The manifest is read before the downloads, so that the new files are not taken for
+        // the ones of a data root without manifest, see [Manifest::read_from].
+        let mut manifest = Manifest::read()?;
+        // Versions that are replaced, which may also be the ones of other data roots.
+        let manifests = Manifest::read_all()?;
+        let mut old_versions: BTreeMap<String, String> = BTreeMap::new();
+        for id in ids.iter() {
+            if let Some((_, entry)) = latest_entry(&manifests, id)? {
+                old_versions.insert(id.clone(), entry.version);
+            }
+        }
+        let results: Vec<(String, Result<(u64, String)>)> =
+            futures::stream::iter(ids.into_iter().map(|id| async move {
+                let result = self.fetch_basis_set(&id, None).await;
+                (id, result)
+            }))
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+
+        let mut summary = DownloadSummary::default();
+        for (id, result) in results {
+            match result {
+                Ok((size, version)) => {
+                    let old_version = match manifest.basis_sets.get(&id) {
+                        Some(entry) => Some(entry.version.clone()),
+                        None => old_versions.remove(&id),
+                    };
+                    if let Some(old_version) = old_version.filter(|old| old != &version) {
+                        let revision = metadata.get(&id).and_then(|m| m.versions.get(&version));
+                        summary.changes.push(BasisSetChange {
+                            id: id.clone(),
+                            old_version,
+                            new_version: version.clone(),
+                            revision_date: revision.map(|r| r.revdate.clone()).unwrap_or_default(),
+                            revision_description: revision
+                                .map(|r| r.revdesc.clone())
+                                .unwrap_or_default(),
+                        });
+                    }
+                    manifest.insert(&id, size, &version);
+                    summary.downloaded.push(id);
                 }
+                Err(err) => summary.failed.push((id, format!("{:#}", err))),
             }
-        }))
-        .buffer_unordered(30)
-        .collect::<Vec<()>>();
-        fetches.await;
-        Ok(())
-    }
-
-    pub async fn download_metadata() -> Result<()> {
-        let names = BasisSetExchange::request_names().await?;
-        let json_path = data_path(JSON_BASIS_DICT)?;
-        let bincode_path = data_path(BINCODE_BASIS_DICT)?;
-        let f = BufWriter::new(File::create(&json_path).unwrap());
-        serde_json::to_writer(f, &names)?;
-        let mut f = BufWriter::new(File::create(&bincode_path).unwrap());
-        serialize_into(&mut f, &names)?;
-        Ok(())
-    }
-
-    fn read_names() -> Result<BTreeMap<String, String>> {
-        let path = data_path(BINCODE_BASIS_DICT)?;
-        let data = fs::read(&path).context("Unable to read file")?;
-        bincode::deserialize(&data).context("Could not deserialize basis set names")
-    }
-
-    pub fn read_basis(name: &str) -> Result<InputData> {
-        ensure_data_exist()?;
-        let names = BasisSetExchange::read_names()?;
-        let basis = names
-            .get(&name.to_lowercase())
-            .context("The basis set could not be found")?;
-        let path = data_path(&format!("{}{}", BASIS_SUBDIRECTORY, basis))?;
-        let data = fs::read_to_string(&path).context("Unable to read basis set file")?;
-        let result = serde_json::from_str::<InputData>(&data)?;
-        Ok(result)
+        }
+        manifest.write()?;
+        summary.downloaded.sort();
+        summary.failed.sort();
+        summary.changes.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(summary)
+    }
+
+    /// Download all basis sets that are listed by the BSE.
+    pub async fn download_basis_sets(&self) -> Result<DownloadSummary> {
+        let metadata = self.request_metadata().await?;
+        self.download_ids(metadata.keys().cloned().collect(), &metadata)
+            .await
     }
+
+    /// Download all basis sets of the local dictionary that are missing or incomplete in all
+    /// data roots.
+    pub async fn download_missing_basis_sets(&self) -> Result<DownloadSummary> {
+        let manifests = Manifest::read_all()?;
+        let mut missing: Vec<String> = Vec::new();
+        for id in BasisSetExchange::read_names()?.into_values() {
+            if !is_complete_in(&manifests, &id)? {
+                missing.push(id);
+            }
+        }
+        let metadata = BasisSetExchange::read_metadata().unwrap_or_default();
+        self.download_ids(missing, &metadata).await
+    }
+
+    /// Download the basis sets of the writable data root, whose file is incomplete or whose
+    /// version differs from the latest version in the given metadata.
+    pub async fn download_outdated_basis_sets(
+        &self,
+        metadata: &BTreeMap<String, InputMetaData>,
+    ) -> Result<DownloadSummary> {
+        // Outdated basis sets of read-only roots are downloaded to the writable root.
+        let manifests = Manifest::read_all()?;
+        let ids: BTreeSet<&String> = manifests
+            .iter()
+            .flat_map(|manifest| manifest.basis_sets.keys())
+            .collect();
+        let mut outdated: Vec<String> = Vec::new();
+        for id in ids {
+            let latest = match metadata.get(id) {
+                Some(meta) => &meta.latest_version,
+                None => continue,
+            };
+            match latest_entry(&manifests, id)? {
+                Some((_, entry)) if &entry.version == latest => {}
+                _ => outdated.push(id.clone()),
+            }
+        }
+        self.download_ids(outdated, metadata).await
+    }
+
+    /// Download a single basis set, given its BSE id.
+    pub async fn download_basis_set(&self, id: &str) -> Result<()> {
+        let (size, version) = self.fetch_basis_set(id, None).await?;
+        let mut manifest = Manifest::read()?;
+        manifest.insert(id, size, &version);
+        manifest.write()
+    }
+
+    /// Download a specific version of a basis set, given its BSE id.
+    pub async fn download_basis_set_version(&self, id: &str, version: &str) -> Result<()> {
+        let (size, _) = self.fetch_basis_set(id, Some(version)).await?;
+        let mut manifest = Manifest::read()?;
+        manifest.insert(&versioned_id(id, version), size, version);
+        manifest.write()
+    }
+
+    /// Download the metadata of all basis sets and the dictionary of their names.
+    pub async fn download_metadata(&self) -> Result<BTreeMap<String, InputMetaData>> {
+        let metadata = self.request_metadata().await?;
+        let names: BTreeMap<String, String> = metadata
+            .iter()
+            .map(|(k, v)| (v.basename.to_lowercase(), k.clone()))
+            .collect();
+        write_atomic(data_path(JSON_METADATA)?, &serde_json::to_vec(&metadata)?)?;
+        write_atomic(data_path(JSON_BASIS_DICT)?, &serde_json::to_vec(&names)?)?;
+        write_atomic(data_path(BINCODE_BASIS_DICT)?, &bincode::serialize(&names)?)?;
+        Ok(metadata)
+    }
+
+    /// Download the bibliographic records of a basis set, given its BSE id.
+    pub async fn download_references(&self, id: &str) -> Result<()> {
+        let url = format!(
+            "{}{}{}{}",
+            self.base_url, BSE_REFERENCES, id, BSE_REFERENCES_SUFFIX
+        );
+        let bytes = self.fetch(&url).await?;
+        let sets: Vec<InputReferenceSet> =
+            serde_json::from_slice(&bytes).context("The references are invalid")?;
+        write_atomic(references_path(id)?, &serde_json::to_vec(&records(&sets))?)
+            .context("Could not write the references")
+    }
+
+    /// The bibliographic records of a basis set by their key, given its BSE id.
+    ///
+    /// They are only downloaded, if they are not cached yet.
+    pub fn references(&self, id: &str) -> Result<BTreeMap<String, Reference>, AtoError> {
+        ensure_references_exist(self, id)
+            .map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+        let path = read_path(&references_suffix(id))?;
+        Ok(serde_json::from_slice(&fs::read(&path)?)?)
+    }
+
+    /// Whether the id is the one of a basis set of the BSE and not of a custom basis set.
+    pub(crate) fn is_bse_basis(&self, id: &str) -> Result<bool, AtoError> {
+        if custom_basis(id)?.is_some() {
+            return Ok(false);
+        }
+        ensure_metadata_exist(self).map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+        Ok(BasisSetExchange::read_metadata()?.contains_key(id))
+    }
+
+    pub(crate) fn read_metadata() -> Result<BTreeMap<String, InputMetaData>, AtoError> {
+        let path = read_path(JSON_METADATA)?;
+        let data = fs::read(&path)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    pub(crate) fn read_names() -> Result<BTreeMap<String, String>, AtoError> {
+        let path = read_path(BINCODE_BASIS_DICT)?;
+        let data = fs::read(&path)?;
+        Ok(bincode::deserialize(&data)?)
+    }
+
+    /// Read a basis set by its name. Only this basis set is downloaded, if it is not present.
+    pub fn read_basis(name: &str) -> Result<InputData, AtoError> {
+        BasisSetExchange::from_env()?.load(name)
+    }
+
+    /// The data root from which a basis set is loaded, or `None` if it was not downloaded yet.
+    ///
+    /// With several roots in $ATO_DATA_PATH, this is the layer with the latest version of the
+    /// basis set, see [crate::files::basis_root].
+    pub fn locate(&self, name: &str) -> Result<Option<PathBuf>, AtoError> {
+        if let Some((root, _, _)) = custom_basis(name)? {
+            return Ok(Some(root));
+        }
+        let id = self.resolve(name)?;
+        basis_root(&id).map_err(data_path_error)
+    }
+}
+
+/// Path of a file in the writable data root or else the first other root that contains it, see
+/// [find_data_path]. The path in the writable root is returned, if none contains it.
+fn read_path(suffix: &str) -> Result<PathBuf, AtoError> {
+    match find_data_path(suffix).map_err(data_path_error)? {
+        Some(path) => Ok(path),
+        None => data_path(suffix).map_err(data_path_error),
+    }
+}
+
+/// The custom basis sets of all data roots that have some, together with their root.
+fn custom_sources() -> Result<Vec<(PathBuf, DirectorySource)>, AtoError> {
+    Ok(data_roots()
+        .map_err(data_path_error)?
+        .into_iter()
+        .filter(|root| root.join(CUSTOM_SUBDIRECTORY).is_dir())
+        .map(|root| {
+            let source = DirectorySource::new(root.join(CUSTOM_SUBDIRECTORY));
+            (root, source)
+        })
+        .collect())
+}
+
+/// The custom basis set with the given name and the data root in which it was found. Custom
+/// basis sets take precedence over the ones of the BSE, so only a name that none of them knows
+/// falls through to the BSE. Unreadable custom basis sets are an error.
+fn custom_basis(name: &str) -> Result<Option<(PathBuf, DirectorySource, String)>, AtoError> {
+    for (root, source) in custom_sources()? {
+        match source.resolve(name) {
+            Ok(id) => return Ok(Some((root, source, id))),
+            Err(AtoError::UnknownBasis { .. }) => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(None)
+}
+
+impl BasisSource for BasisSetExchange {
+    fn names(&self) -> Result<Vec<String>, AtoError> {
+        ensure_metadata_exist(self).map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+        let mut names: Vec<String> = BasisSetExchange::read_names()?.into_keys().collect();
+        for (_, custom) in custom_sources()? {
+            names.append(&mut custom.names()?);
+        }
+        Ok(names)
+    }
+
+    /// Resolve the name, display name or any other name of a basis set to its BSE id.
+    fn resolve(&self, name: &str) -> Result<String, AtoError> {
+        if let Some((_, _, id)) = custom_basis(name)? {
+            return Ok(id);
+        }
+        ensure_metadata_exist(self).map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+        name_index()?.resolve(name)
+    }
+
+    fn load(&self, name: &str) -> Result<InputData, AtoError> {
+        if let Some((_, source, id)) = custom_basis(name)? {
+            return source.load(&id);
+        }
+        let basis = self.resolve(name)?;
+        ensure_basis_exist(self, &basis).map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+        read_basis_file(&basis)
+    }
+
+    fn load_version(&self, name: &str, version: &str) -> Result<InputData, AtoError> {
+        if let Some((_, source, id)) = custom_basis(name)? {
+            return source.load_version(&id, version);
+        }
+        let basis = self.resolve(name)?;
+        check_version(name, &basis, version)?;
+        let id = if is_latest(&basis, version)? {
+            basis
+        } else {
+            ensure_basis_version_exist(self, &basis, version)
+                .map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+            versioned_id(&basis, version)
+        };
+        read_version_file(name, &id, version)
+    }
+}
+
+/// Async variants of the [BasisSource] methods, which can be used inside of a Tokio runtime.
+impl BasisSetExchange {
+    /// Async variant of [BasisSource::resolve].
+    pub async fn resolve_async(&self, name: &str) -> Result<String, AtoError> {
+        if let Some((_, _, id)) = custom_basis(name)? {
+            return Ok(id);
+        }
+        ensure_metadata_exist_async(self)
+            .await
+            .map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+        name_index()?.resolve(name)
+    }
+
+    /// Async variant of [BasisSource::load].
+    pub async fn load_async(&self, name: &str) -> Result<InputData, AtoError> {
+        if let Some((_, source, id)) = custom_basis(name)? {
+            return source.load(&id);
+        }
+        let basis = self.resolve_async(name).await?;
+        ensure_basis_exist_async(self, &basis)
+            .await
+            .map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+        read_basis_file(&basis)
+    }
+
+    /// Async variant of [BasisSource::load_version].
+    pub async fn load_version_async(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<InputData, AtoError> {
+        if let Some((_, source, id)) = custom_basis(name)? {
+            return source.load_version(&id, version);
+        }
+        let basis = self.resolve_async(name).await?;
+        check_version(name, &basis, version)?;
+        let id = if is_latest(&basis, version)? {
+            basis
+        } else {
+            ensure_basis_version_exist_async(self, &basis, version)
+                .await
+                .map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+            versioned_id(&basis, version)
+        };
+        read_version_file(name, &id, version)
+    }
+}
+
+/// Index of all names of the basis sets in the local metadata.
+fn name_index() -> Result<NameIndex, AtoError> {
+    match BasisSetExchange::read_metadata() {
+        Ok(metadata) => Ok(Catalogue::from_metadata(metadata).name_index()),
+        // Data directories of older versions of this crate only contain the basenames.
+        Err(_) => {
+            let mut index = NameIndex::new();
+            for (basename, id) in BasisSetExchange::read_names()? {
+                index.insert(&id, &basename);
+            }
+            Ok(index)
+        }
+    }
+}
+
+fn unknown_version(name: &str, version: &str) -> AtoError {
+    AtoError::UnknownVersion {
+        basis: name.to_owned(),
+        version: version.to_owned(),
+    }
+}
+
+/// Check that the version of a basis set is listed in the local metadata.
+fn check_version(name: &str, basis: &str, version: &str) -> Result<(), AtoError> {
+    // The metadata is missing in data directories of older versions of this crate.
+    if let Ok(metadata) = BasisSetExchange::read_metadata() {
+        match metadata.get(basis) {
+            Some(meta) if !meta.versions.contains_key(version) => {
+                return Err(unknown_version(name, version))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// True if the requested version is the latest one that was downloaded, which is used then.
+fn is_latest(basis: &str, version: &str) -> Result<bool, AtoError> {
+    let manifest = match basis_root(basis).map_err(data_path_error)? {
+        Some(root) => Manifest::read_from(&root),
+        None => Manifest::read(),
+    }
+    .map_err(|err| AtoError::Parse(format!("{:#}", err)))?;
+    let latest = manifest
+        .basis_sets
+        .get(basis)
+        .is_some_and(|entry| entry.version == version);
+    Ok(latest && manifest.is_complete(basis).unwrap_or(false))
+}
+
+/// Read a pinned version of a basis set file and check its version.
+fn read_version_file(name: &str, id: &str, version: &str) -> Result<InputData, AtoError> {
+    let data = read_basis_file(id)?;
+    if data.version != version {
+        return Err(unknown_version(name, version));
+    }
+    Ok(data)
+}
+
+/// Read a basis set file from the first data root in which it is complete.
+fn read_basis_file(id: &str) -> Result<InputData, AtoError> {
+    let path = match basis_root(id).map_err(data_path_error)? {
+        Some(root) => root.join(format!("{}{}.json", BASIS_SUBDIRECTORY, id)),
+        None => basis_path(id).map_err(data_path_error)?,
+    };
+    let data = fs::read_to_string(&path)?;
+    let result = serde_json::from_str::<InputData>(&data)?;
+    Ok(result)
+}
+
+/// The version of a basis set file, without the remaining data.
+#[derive(Deserialize)]
+pub(crate) struct InputVersion {
+    pub(crate) version: String,
+}
+
+/// Connection errors, server errors and rate limits are worth a retry, client errors are not.
+fn is_transient(err: &reqwest::Error) -> bool {
+    match err.status() {
+        Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+        None => true,
+    }
+}
+
+/// The data path can only fail if the home directory could not be resolved or none of the data
+/// roots is writable.
+fn data_path_error(err: anyhow::Error) -> AtoError {
+    AtoError::Io(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{:#}", err),
+    ))
 }
@@ -1,28 +1,31 @@
+use crate::bse::basisset_json::InputAuxiliary;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InputMetaData {
+    #[serde(default)]
+    pub(crate) auxiliaries: BTreeMap<String, InputAuxiliary>,
     pub(crate) basename: String,
-    description: String,
-    display_name: String,
-    family: String,
-    function_types: Vec<String>,
-    latest_version: String,
-    notes_exist: Vec<bool>,
-    other_names: Vec<String>,
-    relpath: String,
-    role: String,
-    tags: Vec<String>,
-    versions: BTreeMap<String, InputVersions>,
+    pub(crate) description: String,
+    pub(crate) display_name: String,
+    pub(crate) family: String,
+    pub(crate) function_types: Vec<String>,
+    pub(crate) latest_version: String,
+    pub(crate) notes_exist: Vec<bool>,
+    pub(crate) other_names: Vec<String>,
+    pub(crate) relpath: String,
+    pub(crate) role: String,
+    pub(crate) tags: Vec<String>,
+    pub(crate) versions: BTreeMap<String, InputVersions>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct InputVersions {
-    elements: Vec<String>,
-    file_relpath: String,
-    revdate: String,
-    revdesc: String,
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct InputVersions {
+    pub(crate) elements: Vec<String>,
+    pub(crate) file_relpath: String,
+    pub(crate) revdate: String,
+    pub(crate) revdesc: String,
 }
 
 #[cfg(test)]
@@ -71,5 +74,4 @@ mod tests {
         assert_eq!(&data.display_name, "STO-2G");
         assert_eq!(data.notes_exist, vec![true, true]);
     }
-
 }
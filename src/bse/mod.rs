@@ -1,4 +1,7 @@
 pub mod basisset_json;
-mod basisset_names;
+pub mod basisset_names;
+pub mod checkout;
 pub mod http;
-mod metadata_json;
+pub(crate) mod metadata_json;
+pub(crate) mod references_json;
+pub mod source;
@@ -0,0 +1,73 @@
+use crate::references::Reference;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// References of a group of elements, as returned by the references endpoint of the BSE.
+///
+/// Only the records are read, the descriptions are part of the basis set data.
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct InputReferenceSet {
+    pub(crate) reference_info: Vec<InputReferenceInfo>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub(crate) struct InputReferenceInfo {
+    pub(crate) reference_data: InputReferenceData,
+}
+
+/// The records by their key, which the BSE writes as list of pairs.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub(crate) enum InputReferenceData {
+    Pairs(Vec<(String, Map<String, Value>)>),
+    Keyed(BTreeMap<String, Map<String, Value>>),
+}
+
+/// All records of the response by their key.
+pub(crate) fn records(sets: &[InputReferenceSet]) -> BTreeMap<String, Reference> {
+    let mut records: BTreeMap<String, Reference> = BTreeMap::new();
+    for info in sets.iter().flat_map(|set| set.reference_info.iter()) {
+        let pairs: Vec<(&String, &Map<String, Value>)> = match &info.reference_data {
+            InputReferenceData::Pairs(pairs) => pairs.iter().map(|(k, v)| (k, v)).collect(),
+            InputReferenceData::Keyed(keyed) => keyed.iter().collect(),
+        };
+        for (key, record) in pairs {
+            records
+                .entry(key.clone())
+                .or_insert_with(|| Reference::from_bse(key, record));
+        }
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bse::references_json::{records, InputReferenceSet};
+    use serde_json::from_str;
+
+    #[test]
+    fn test_references() {
+        let sets: Vec<InputReferenceSet> = from_str(
+            r#"[{
+                "reference_info": [{
+                    "reference_description": "STO-2G Minimal Basis (2 functions/AO)",
+                    "reference_data": [["hehre1969a", {
+                        "_entry_type": "article",
+                        "authors": ["Hehre, W. J.", "Stewart, R. F.", "Pople, J. A."],
+                        "title": "Self-Consistent Molecular-Orbital Methods. I.",
+                        "journal": "J. Chem. Phys.",
+                        "volume": "51",
+                        "pages": "2657-2664",
+                        "year": "1969",
+                        "doi": "10.1063/1.1672392"
+                    }]]
+                }],
+                "elements": ["1", "2"]
+            }]"#,
+        )
+        .unwrap();
+        let records = records(&sets);
+        assert_eq!(records["hehre1969a"].fields["year"], "1969");
+    }
+}
@@ -0,0 +1,285 @@
+use crate::bse::basisset_json::InputData;
+use crate::bse::basisset_names::NameIndex;
+use crate::error::AtoError;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+/// A provider of basis set data in the BSE JSON format.
+///
+/// Names are compared case-insensitively. Besides the main name of a basis set, a source may
+/// know further aliases that resolve to the same basis set.
+pub trait BasisSource {
+    /// Names of all basis sets that are provided by the source.
+    fn names(&self) -> Result<Vec<String>, AtoError>;
+
+    /// Resolve a name or alias to the identifier of the basis set within this source.
+    fn resolve(&self, name: &str) -> Result<String, AtoError>;
+
+    /// Load the data of a basis set by its name or alias.
+    fn load(&self, name: &str) -> Result<InputData, AtoError>;
+
+    /// Load a specific version of a basis set.
+    ///
+    /// The default implementation loads the basis set and returns an error if it has another
+    /// version.
+    fn load_version(&self, name: &str, version: &str) -> Result<InputData, AtoError> {
+        let data = self.load(name)?;
+        if data.version != version {
+            return Err(AtoError::UnknownVersion {
+                basis: name.to_owned(),
+                version: version.to_owned(),
+            });
+        }
+        Ok(data)
+    }
+}
+
+/// The names of a basis set, without the remaining data of the file.
+#[derive(Deserialize)]
+struct InputNames {
+    name: String,
+    #[serde(default)]
+    names: Vec<String>,
+}
+
+/// A directory that contains one BSE JSON file per basis set.
+///
+/// The file stem (e.g. `sto-3g` for `sto-3g.json`) is the identifier of the basis set. The
+/// `name` and `names` entries of the files are used as aliases.
+#[derive(Debug, Clone)]
+pub struct DirectorySource {
+    path: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Identifiers of all JSON files in the directory.
+    fn ids(&self) -> Result<Vec<String>, AtoError> {
+        let mut ids: Vec<String> = Vec::new();
+        for entry in fs::read_dir(&self.path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                ids.push(stem.to_owned());
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    fn read(&self, id: &str) -> Result<String, AtoError> {
+        Ok(fs::read_to_string(self.path.join(format!("{}.json", id)))?)
+    }
+
+    /// Index of the names and aliases of all basis sets. The file stems are always included.
+    fn name_index(&self, with_aliases: bool) -> Result<NameIndex, AtoError> {
+        let ids = self.ids()?;
+        let mut index = NameIndex::new();
+        for id in ids.iter() {
+            index.insert(id, id);
+        }
+        if with_aliases {
+            for id in ids.iter() {
+                let names: InputNames = serde_json::from_str(&self.read(id)?)?;
+                index.insert(id, &names.name);
+                for alias in names.names.iter() {
+                    index.insert(id, alias);
+                }
+            }
+        }
+        Ok(index)
+    }
+}
+
+impl BasisSource for DirectorySource {
+    fn names(&self) -> Result<Vec<String>, AtoError> {
+        self.ids()?
+            .iter()
+            .map(|id| Ok(serde_json::from_str::<InputNames>(&self.read(id)?)?.name))
+            .collect()
+    }
+
+    fn resolve(&self, name: &str) -> Result<String, AtoError> {
+        // The file names are checked first, as this does not require to parse any file.
+        if let Some(id) = self.name_index(false)?.get(name) {
+            return Ok(id.to_owned());
+        }
+        self.name_index(true)?.resolve(name)
+    }
+
+    fn load(&self, name: &str) -> Result<InputData, AtoError> {
+        let id = self.resolve(name)?;
+        Ok(serde_json::from_str(&self.read(&id)?)?)
+    }
+}
+
+/// Basis sets that are held in memory, e.g. embedded into the binary with `include_str!`.
+///
+/// ```
+/// use ato::bse::source::{BasisSource, MemorySource};
+/// let mut source = MemorySource::new();
+/// source.insert_json(r#"{"name": "empty", "names": ["empty"], "description": "",
+///     "revision_description": "", "revision_date": "", "version": "1", "function_types": [],
+///     "tags": [], "family": "", "role": "orbital", "elements": {}}"#).unwrap();
+/// assert_eq!(source.names().unwrap(), vec!["empty".to_string()]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemorySource {
+    basis_sets: Vec<InputData>,
+}
+
+impl MemorySource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the data of a basis set.
+    pub fn insert(&mut self, data: InputData) {
+        self.basis_sets.push(data);
+    }
+
+    /// Add a basis set from its BSE JSON representation.
+    pub fn insert_json(&mut self, json: &str) -> Result<(), AtoError> {
+        self.insert(serde_json::from_str(json)?);
+        Ok(())
+    }
+
+    fn name_index(&self) -> NameIndex {
+        let mut index = NameIndex::new();
+        for data in self.basis_sets.iter() {
+            index.insert(&data.name, &data.name);
+        }
+        for data in self.basis_sets.iter() {
+            for alias in data.names.iter() {
+                index.insert(&data.name, alias);
+            }
+        }
+        index
+    }
+}
+
+impl BasisSource for MemorySource {
+    fn names(&self) -> Result<Vec<String>, AtoError> {
+        Ok(self
+            .basis_sets
+            .iter()
+            .map(|data| data.name.clone())
+            .collect())
+    }
+
+    fn resolve(&self, name: &str) -> Result<String, AtoError> {
+        self.name_index().resolve(name)
+    }
+
+    fn load(&self, name: &str) -> Result<InputData, AtoError> {
+        let id = self.resolve(name)?;
+        self.basis_sets
+            .iter()
+            .find(|data| data.name == id)
+            .cloned()
+            .ok_or_else(|| AtoError::UnknownBasis {
+                name: name.to_owned(),
+                suggestions: Vec::new(),
+            })
+    }
+
+    fn load_version(&self, name: &str, version: &str) -> Result<InputData, AtoError> {
+        let id = self.resolve(name)?;
+        self.basis_sets
+            .iter()
+            .find(|data| data.name == id && data.version == version)
+            .cloned()
+            .ok_or_else(|| AtoError::UnknownVersion {
+                basis: name.to_owned(),
+                version: version.to_owned(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bse::source::{BasisSource, DirectorySource, MemorySource};
+    use crate::error::AtoError;
+    use std::fs;
+
+    const STO2G: &str = r#"
+    {
+        "revision_description": "Data from Gaussian09",
+        "revision_date": "2018-06-19",
+        "elements": {
+            "1": {
+                "electron_shells": [
+                    {
+                        "function_type": "gto",
+                        "region": "",
+                        "angular_momentum": [0],
+                        "exponents": ["0.1309756377E+01","0.2331359749E+00"],
+                        "coefficients": [["0.4301284983E+00","0.6789135305E+00"]]
+                    }
+                ],
+                "references": []
+            }
+        },
+        "version": "1",
+        "function_types": ["gto"],
+        "names": ["STO-2G", "sto2g"],
+        "tags": [],
+        "family": "sto",
+        "description": "STO-2G Minimal Basis (2 functions/AO)",
+        "role": "orbital",
+        "name": "STO-2G"
+    }"#;
+
+    #[test]
+    fn test_memory_source() {
+        let mut source = MemorySource::new();
+        source.insert_json(STO2G).unwrap();
+        assert_eq!(source.names().unwrap(), vec!["STO-2G".to_string()]);
+        assert_eq!(source.resolve("sto2g").unwrap(), "STO-2G".to_string());
+        assert_eq!(source.load("sto-2g").unwrap().family, "sto".to_string());
+        assert!(matches!(
+            source.load("sto-3g"),
+            Err(AtoError::UnknownBasis { .. })
+        ));
+    }
+
+    #[test]
+    fn test_memory_source_versions() {
+        let mut source = MemorySource::new();
+        source.insert_json(STO2G).unwrap();
+        source
+            .insert_json(&STO2G.replace(r#""version": "1""#, r#""version": "0""#))
+            .unwrap();
+        assert_eq!(source.load_version("sto-2g", "0").unwrap().version, "0");
+        assert_eq!(source.load_version("sto-2g", "1").unwrap().version, "1");
+        assert!(matches!(
+            source.load_version("sto-2g", "2"),
+            Err(AtoError::UnknownVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_directory_source() {
+        let dir = std::env::temp_dir().join(format!("ato_directory_source_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("sto-2g.json"), STO2G).unwrap();
+        fs::write(dir.join("notes.txt"), "not a basis set").unwrap();
+
+        let source = DirectorySource::new(&dir);
+        assert_eq!(source.names().unwrap(), vec!["STO-2G".to_string()]);
+        assert_eq!(source.resolve("STO-2G").unwrap(), "sto-2g".to_string());
+        assert_eq!(source.resolve("Sto2G").unwrap(), "sto-2g".to_string());
+        assert_eq!(source.load("sto2g").unwrap().name, "STO-2G".to_string());
+        assert!(matches!(
+            source.resolve("sto-3g"),
+            Err(AtoError::UnknownBasis { .. })
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,459 @@
+use crate::bse::basisset_names::NameIndex;
+use crate::bse::http::BasisSetExchange;
+use crate::bse::metadata_json::InputMetaData;
+use crate::elements::Element;
+use crate::error::AtoError;
+use crate::files::{ensure_catalogue_exist, ensure_catalogue_exist_async};
+use std::collections::BTreeMap;
+
+/// Overview of all basis sets that are available at the Basis Set Exchange.
+///
+/// The catalogue is built from the BSE metadata and can be searched by family, role, function
+/// types, tags and the elements that are covered:
+///
+/// ```no_run
+/// use ato::catalogue::{Catalogue, CatalogueFilter};
+/// use ato::elements::Element;
+/// let catalogue = Catalogue::load().unwrap();
+/// let filter = CatalogueFilter::new()
+///     .family("dunning")
+///     .role("orbital")
+///     .elements(&[Element::Br, Element::I]);
+/// for entry in catalogue.filter(&filter) {
+///     println!("{}", entry.name);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Catalogue {
+    entries: BTreeMap<String, CatalogueEntry>,
+}
+
+/// Metadata of a single basis set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogueEntry {
+    /// Identifier of the basis set at the BSE.
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+    pub description: String,
+    pub family: String,
+    /// Role of the basis set, e.g. `orbital`, `jkfit`, `rifit` or `optri`.
+    pub role: String,
+    /// Function types, e.g. `gto`, `gto_spherical` or `scalar_ecp`.
+    pub function_types: Vec<String>,
+    pub tags: Vec<String>,
+    /// Alternative names of the basis set.
+    pub other_names: Vec<String>,
+    pub latest_version: String,
+    pub versions: BTreeMap<String, CatalogueVersion>,
+    /// Names of the auxiliary basis sets by their role, e.g. `jkfit`.
+    pub auxiliaries: BTreeMap<String, Vec<String>>,
+}
+
+/// A single version of a basis set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogueVersion {
+    pub elements: Vec<Element>,
+    pub revision_date: String,
+    pub revision_description: String,
+    /// Path of the table file of this version in the BSE data repository.
+    pub file_relpath: String,
+}
+
+impl Catalogue {
+    /// Catalogue of the default endpoint. The metadata is downloaded if it does not exist yet.
+    pub fn load() -> Result<Self, AtoError> {
+        Self::from_exchange(&BasisSetExchange::from_env()?)
+    }
+
+    /// Catalogue of a configured [BasisSetExchange].
+    pub fn from_exchange(bse: &BasisSetExchange) -> Result<Self, AtoError> {
+        ensure_catalogue_exist(bse).map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+        Ok(Self::from_metadata(BasisSetExchange::read_metadata()?))
+    }
+
+    /// Async variant of [Catalogue::from_exchange], for use inside of a Tokio runtime.
+    pub async fn from_exchange_async(bse: &BasisSetExchange) -> Result<Self, AtoError> {
+        ensure_catalogue_exist_async(bse)
+            .await
+            .map_err(|err| AtoError::Download(format!("{:#}", err)))?;
+        Ok(Self::from_metadata(BasisSetExchange::read_metadata()?))
+    }
+
+    /// Catalogue from the JSON response of the BSE metadata endpoint.
+    pub fn from_json(json: &str) -> Result<Self, AtoError> {
+        Ok(Self::from_metadata(serde_json::from_str(json)?))
+    }
+
+    pub(crate) fn from_metadata(metadata: BTreeMap<String, InputMetaData>) -> Self {
+        let entries = metadata
+            .into_iter()
+            .map(|(id, meta)| {
+                let versions = meta
+                    .versions
+                    .into_iter()
+                    .map(|(version, data)| {
+                        let elements = data
+                            .elements
+                            .iter()
+                            .filter_map(|number| number.parse::<u8>().ok())
+                            .map(Element::from)
+                            .collect();
+                        let version_entry = CatalogueVersion {
+                            elements,
+                            revision_date: data.revdate,
+                            revision_description: data.revdesc,
+                            file_relpath: data.file_relpath,
+                        };
+                        (version, version_entry)
+                    })
+                    .collect();
+                let entry = CatalogueEntry {
+                    id: id.clone(),
+                    name: meta.basename,
+                    display_name: meta.display_name,
+                    description: meta.description,
+                    family: meta.family,
+                    role: meta.role,
+                    function_types: meta.function_types,
+                    tags: meta.tags,
+                    other_names: meta.other_names,
+                    latest_version: meta.latest_version,
+                    versions,
+                    auxiliaries: meta
+                        .auxiliaries
+                        .iter()
+                        .map(|(role, auxiliary)| (role.clone(), auxiliary.names()))
+                        .collect(),
+                };
+                (id, entry)
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Entry of a basis set by its BSE id.
+    pub fn get(&self, id: &str) -> Option<&CatalogueEntry> {
+        self.entries.get(id)
+    }
+
+    /// Entry of a basis set by its name or any of its aliases.
+    ///
+    /// If no basis set matches, the error contains the most similar names.
+    pub fn resolve(&self, name: &str) -> Result<&CatalogueEntry, AtoError> {
+        let id = self.name_index().resolve(name)?;
+        Ok(&self.entries[&id])
+    }
+
+    /// The auxiliary basis set of a basis set for a role, e.g. `jkfit`, `rifit` or `optri`.
+    pub fn auxiliary(&self, name: &str, role: &str) -> Result<&CatalogueEntry, AtoError> {
+        let entry = self.resolve(name)?;
+        self.companion(&entry.name, &entry.auxiliaries, role)
+    }
+
+    /// The first of the auxiliary basis sets listed for the role, whose own role matches.
+    ///
+    /// Roles are compared case-insensitively. If there is none, the error lists the roles of
+    /// the given auxiliary basis sets.
+    pub(crate) fn companion(
+        &self,
+        basis: &str,
+        auxiliaries: &BTreeMap<String, Vec<String>>,
+        role: &str,
+    ) -> Result<&CatalogueEntry, AtoError> {
+        let index = self.name_index();
+        let found = auxiliaries
+            .iter()
+            .filter(|(listed, _)| listed.eq_ignore_ascii_case(role))
+            .flat_map(|(_, names)| names.iter())
+            .filter_map(|name| index.get(name))
+            .map(|id| &self.entries[id])
+            .find(|entry| entry.role.eq_ignore_ascii_case(role));
+        found.ok_or_else(|| AtoError::MissingAuxiliary {
+            basis: basis.to_owned(),
+            role: role.to_owned(),
+            roles: auxiliaries.keys().cloned().collect(),
+        })
+    }
+
+    /// Index of the names, display names and other names of all basis sets.
+    ///
+    /// The names are inserted in this order, so that an alias never hides the name of
+    /// another basis set.
+    pub fn name_index(&self) -> NameIndex {
+        let mut index = NameIndex::new();
+        for entry in self.entries() {
+            index.insert(&entry.id, &entry.name);
+        }
+        for entry in self.entries() {
+            index.insert(&entry.id, &entry.id);
+            index.insert(&entry.id, &entry.display_name);
+        }
+        for entry in self.entries() {
+            for alias in entry.other_names.iter() {
+                index.insert(&entry.id, alias);
+            }
+        }
+        index
+    }
+
+    /// All entries, ordered by their BSE id.
+    pub fn entries(&self) -> impl Iterator<Item = &CatalogueEntry> {
+        self.entries.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All entries that match the filter.
+    pub fn filter(&self, filter: &CatalogueFilter) -> Vec<&CatalogueEntry> {
+        self.entries()
+            .filter(|entry| filter.matches(entry))
+            .collect()
+    }
+
+    /// Names of all families, without duplicates.
+    pub fn families(&self) -> Vec<&str> {
+        let mut families: Vec<&str> = self.entries().map(|e| e.family.as_str()).collect();
+        families.sort_unstable();
+        families.dedup();
+        families
+    }
+
+    /// Names of all roles, without duplicates.
+    pub fn roles(&self) -> Vec<&str> {
+        let mut roles: Vec<&str> = self.entries().map(|e| e.role.as_str()).collect();
+        roles.sort_unstable();
+        roles.dedup();
+        roles
+    }
+}
+
+impl CatalogueEntry {
+    /// Elements that are covered by a version. The latest version is used if none is given.
+    pub fn elements(&self, version: Option<&str>) -> Option<&[Element]> {
+        self.versions
+            .get(version.unwrap_or(&self.latest_version))
+            .map(|v| v.elements.as_slice())
+    }
+
+    /// Check that a version of the basis set covers all elements.
+    pub fn covers(&self, elements: &[Element], version: Option<&str>) -> bool {
+        match self.elements(version) {
+            Some(covered) => elements.iter().all(|el| covered.contains(el)),
+            None => false,
+        }
+    }
+}
+
+/// Conditions for the search in a [Catalogue]. All strings are compared case-insensitively and
+/// only the conditions that were set are checked.
+#[derive(Debug, Clone, Default)]
+pub struct CatalogueFilter {
+    family: Option<String>,
+    role: Option<String>,
+    function_types: Vec<String>,
+    tags: Vec<String>,
+    elements: Vec<Element>,
+    version: Option<String>,
+}
+
+impl CatalogueFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn family(mut self, family: &str) -> Self {
+        self.family = Some(family.to_lowercase());
+        self
+    }
+
+    pub fn role(mut self, role: &str) -> Self {
+        self.role = Some(role.to_lowercase());
+        self
+    }
+
+    /// Require a function type. Can be called several times to require several types.
+    pub fn function_type(mut self, function_type: &str) -> Self {
+        self.function_types.push(function_type.to_lowercase());
+        self
+    }
+
+    /// Require a tag. Can be called several times to require several tags.
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_lowercase());
+        self
+    }
+
+    /// Require that all elements are covered.
+    pub fn elements(mut self, elements: &[Element]) -> Self {
+        self.elements.extend_from_slice(elements);
+        self
+    }
+
+    /// Check the element coverage of this version instead of the latest one.
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_owned());
+        self
+    }
+
+    pub fn matches(&self, entry: &CatalogueEntry) -> bool {
+        let contains =
+            |values: &[String], value: &String| values.iter().any(|v| &v.to_lowercase() == value);
+        if let Some(family) = &self.family {
+            if &entry.family.to_lowercase() != family {
+                return false;
+            }
+        }
+        if let Some(role) = &self.role {
+            if &entry.role.to_lowercase() != role {
+                return false;
+            }
+        }
+        if !self
+            .function_types
+            .iter()
+            .all(|ft| contains(&entry.function_types, ft))
+            || !self.tags.iter().all(|tag| contains(&entry.tags, tag))
+        {
+            return false;
+        }
+        if self.elements.is_empty() && self.version.is_none() {
+            return true;
+        }
+        entry.covers(&self.elements, self.version.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::catalogue::{Catalogue, CatalogueFilter};
+    use crate::elements::Element;
+    use crate::error::AtoError;
+
+    const METADATA: &str = r#"
+    {
+        "cc-pvdz": {
+            "basename": "cc-pVDZ", "description": "cc-pVDZ", "display_name": "cc-pVDZ",
+            "family": "dunning", "function_types": ["gto", "gto_spherical"],
+            "latest_version": "1", "notes_exist": [true, true], "other_names": [],
+            "relpath": "", "role": "orbital", "tags": [],
+            "auxiliaries": {"jkfit": "cc-pVDZ-JKFIT", "rifit": ["cc-pVDZ-RIFIT", "cc-pVDZ-JKFIT"]},
+            "versions": {
+                "0": {"elements": ["1", "6", "35"], "file_relpath": "cc-pVDZ.0.table.json",
+                      "revdate": "2007-01-15", "revdesc": "Original"},
+                "1": {"elements": ["1", "6", "35", "53"], "file_relpath": "cc-pVDZ.1.table.json",
+                      "revdate": "2018-06-19", "revdesc": "Data from the EMSL"}
+            }
+        },
+        "cc-pvdz-jkfit": {
+            "basename": "cc-pVDZ-JKFIT", "description": "JK fitting", "display_name": "cc-pVDZ-JKFIT",
+            "family": "dunning", "function_types": ["gto", "gto_spherical"],
+            "latest_version": "1", "notes_exist": [false, false], "other_names": [],
+            "relpath": "", "role": "jkfit", "tags": [],
+            "versions": {
+                "1": {"elements": ["1", "6", "35", "53"], "file_relpath": "cc-pVDZ-JKFIT.1.table.json",
+                      "revdate": "2018-06-19", "revdesc": "Data from the EMSL"}
+            }
+        },
+        "def2-svp": {
+            "basename": "def2-SVP", "description": "def2-SVP", "display_name": "Def2-SVP",
+            "family": "ahlrichs", "function_types": ["gto", "gto_spherical", "scalar_ecp"],
+            "latest_version": "1", "notes_exist": [false, false], "other_names": ["def2svp"],
+            "relpath": "", "role": "orbital", "tags": ["ecp"],
+            "versions": {
+                "1": {"elements": ["1", "6"], "file_relpath": "def2-SVP.1.table.json",
+                      "revdate": "2018-06-19", "revdesc": "Data from Turbomole"}
+            }
+        }
+    }"#;
+
+    fn ids(catalogue: &Catalogue, filter: &CatalogueFilter) -> Vec<String> {
+        catalogue
+            .filter(filter)
+            .iter()
+            .map(|entry| entry.id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogue() {
+        let catalogue = Catalogue::from_json(METADATA).unwrap();
+        assert_eq!(catalogue.len(), 3);
+        assert_eq!(catalogue.families(), vec!["ahlrichs", "dunning"]);
+        assert_eq!(catalogue.roles(), vec!["jkfit", "orbital"]);
+        let entry = catalogue.get("def2-svp").unwrap();
+        assert_eq!(entry.display_name, "Def2-SVP");
+        assert_eq!(entry.other_names, vec!["def2svp"]);
+        assert_eq!(entry.elements(None).unwrap(), &[Element::H, Element::C]);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let catalogue = Catalogue::from_json(METADATA).unwrap();
+        assert_eq!(catalogue.resolve("DEF2SVP").unwrap().id, "def2-svp");
+        assert_eq!(
+            catalogue.resolve("cc-pvdz jkfit").unwrap().id,
+            "cc-pvdz-jkfit"
+        );
+        match catalogue.resolve("def2-svpp") {
+            Err(AtoError::UnknownBasis { suggestions, .. }) => {
+                assert_eq!(suggestions[0], "def2-SVP")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_auxiliary() {
+        let catalogue = Catalogue::from_json(METADATA).unwrap();
+        assert_eq!(
+            catalogue.auxiliary("cc-pVDZ", "JKFIT").unwrap().id,
+            "cc-pvdz-jkfit"
+        );
+        // The listed RI basis set is not part of the catalogue and the JK basis set has
+        // another role.
+        match catalogue.auxiliary("cc-pVDZ", "rifit") {
+            Err(AtoError::MissingAuxiliary { basis, roles, .. }) => {
+                assert_eq!(basis, "cc-pVDZ");
+                assert_eq!(roles, vec!["jkfit", "rifit"]);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        let err = catalogue.auxiliary("def2-svp", "optri").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The basis set def2-SVP has no auxiliary basis set for the role optri"
+        );
+    }
+
+    #[test]
+    fn test_filter() {
+        let catalogue = Catalogue::from_json(METADATA).unwrap();
+        let filter = CatalogueFilter::new()
+            .family("Dunning")
+            .role("orbital")
+            .elements(&[Element::Br, Element::I]);
+        assert_eq!(ids(&catalogue, &filter), vec!["cc-pvdz"]);
+
+        // Iodine is only covered by the latest version.
+        let filter = filter.version("0");
+        assert!(ids(&catalogue, &filter).is_empty());
+
+        let filter = CatalogueFilter::new().role("JKFIT");
+        assert_eq!(ids(&catalogue, &filter), vec!["cc-pvdz-jkfit"]);
+
+        let filter = CatalogueFilter::new()
+            .function_type("scalar_ecp")
+            .tag("ecp");
+        assert_eq!(ids(&catalogue, &filter), vec!["def2-svp"]);
+
+        let filter = CatalogueFilter::new().elements(&[Element::C]);
+        assert_eq!(ids(&catalogue, &filter).len(), 3);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::angular_momentum::AngularMomentum;
+use crate::basis_function::OriginalValues;
+
+/// Kind of the terms of an effective core potential.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum EcpType {
+    /// Scalar relativistic potential (`scalar_ecp` on the BSE).
+    Scalar,
+    /// Spin-orbit potential (`spinorbit_ecp` on the BSE).
+    SpinOrbit,
+}
+
+impl EcpType {
+    /// Name of the type as it is used in the BSE JSON format.
+    pub fn bse_name(&self) -> &'static str {
+        match self {
+            EcpType::Scalar => "scalar_ecp",
+            EcpType::SpinOrbit => "spinorbit_ecp",
+        }
+    }
+}
+
+/// One angular momentum projector of an effective core potential.
+///
+/// The potential of the projector is the sum over all terms
+/// `c_i * r^(n_i - 2) * exp(-a_i * r^2)`, where `n_i` are the `r_exponents`, `a_i` the
+/// `exponents` and `c_i` the `coefficients`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EcpProjector {
+    pub l: AngularMomentum,
+    pub kind: EcpType,
+    pub r_exponents: Vec<i32>,
+    pub exponents: Vec<f64>,
+    pub coefficients: Vec<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) original: Option<OriginalValues>,
+}
+
+impl EcpProjector {
+    pub fn new(
+        l: AngularMomentum,
+        kind: EcpType,
+        r_exponents: Vec<i32>,
+        exponents: Vec<f64>,
+        coefficients: Vec<f64>,
+    ) -> Self {
+        Self {
+            l,
+            kind,
+            r_exponents,
+            exponents,
+            coefficients,
+            original: None,
+        }
+    }
+}
+
+/// Effective core potential (pseudopotential) of an element.
+///
+/// As on the BSE, the scalar projector with the highest angular momentum is the local part of
+/// the potential, which acts on all angular momenta. Spin-orbit projectors are never local.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EffectiveCorePotential {
+    /// Number of core electrons that are replaced by the potential.
+    pub core_electrons: usize,
+    pub projectors: Vec<EcpProjector>,
+}
+
+impl EffectiveCorePotential {
+    /// Highest angular momentum of the scalar projectors, i.e. the one of the local part.
+    pub fn max_l(&self) -> Option<AngularMomentum> {
+        self.projectors
+            .iter()
+            .filter(|projector| projector.kind == EcpType::Scalar)
+            .map(|projector| projector.l)
+            .max_by_key(|l| *l as u8)
+    }
+
+    fn is_local(&self, projector: &EcpProjector, max_l: Option<AngularMomentum>) -> bool {
+        projector.kind == EcpType::Scalar && Some(projector.l) == max_l
+    }
+
+    /// The local part of the potential.
+    pub fn local(&self) -> Vec<&EcpProjector> {
+        let max_l = self.max_l();
+        self.projectors
+            .iter()
+            .filter(|projector| self.is_local(projector, max_l))
+            .collect()
+    }
+
+    /// The semi-local projectors, i.e. all projectors except the local part, including all
+    /// spin-orbit projectors.
+    pub fn semi_local(&self) -> Vec<&EcpProjector> {
+        let max_l = self.max_l();
+        self.projectors
+            .iter()
+            .filter(|projector| !self.is_local(projector, max_l))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::angular_momentum::AngularMomentum;
+    use crate::ecp::{EcpProjector, EcpType, EffectiveCorePotential};
+
+    #[test]
+    fn test_spin_orbit_not_local() {
+        let projector = |l: AngularMomentum, kind: EcpType| {
+            EcpProjector::new(l, kind, vec![2], vec![1.0], vec![1.0])
+        };
+        let ecp = EffectiveCorePotential {
+            core_electrons: 10,
+            projectors: vec![
+                projector(AngularMomentum::D, EcpType::Scalar),
+                projector(AngularMomentum::S, EcpType::Scalar),
+                projector(AngularMomentum::F, EcpType::SpinOrbit),
+            ],
+        };
+        assert_eq!(ecp.max_l(), Some(AngularMomentum::D));
+        let local = ecp.local();
+        assert_eq!(local.len(), 1);
+        assert_eq!(local[0].kind, EcpType::Scalar);
+        let semi_local = ecp.semi_local();
+        assert_eq!(semi_local.len(), 2);
+        assert!(semi_local.iter().any(|p| p.kind == EcpType::SpinOrbit));
+    }
+}
@@ -22,3 +22,20 @@ pub enum Shell {
     Nine = 9,
 }
 
+/// The shell is represented by its principal quantum number.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Shell {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Shell {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let n = <u8 as serde::Deserialize>::deserialize(deserializer)?;
+        Shell::try_from(n).map_err(|_| {
+            serde::de::Error::custom(format!("{} is not a principal quantum number", n))
+        })
+    }
+}
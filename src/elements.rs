@@ -36,7 +36,7 @@ impl Eq for Element {}
 // Two elements are compared by their atomic numbers
 impl PartialOrd for Element {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.number().partial_cmp(&other.number())
+        Some(self.cmp(other))
     }
 }
 
@@ -165,9 +165,9 @@ impl From<&str> for Element {
             "bh" => Element::Bh,
             "hs" => Element::Hs,
             "mt" => Element::Mt,
-            "ds " => Element::Ds,
-            "rg " => Element::Rg,
-            "cn " => Element::Cn,
+            "ds" => Element::Ds,
+            "rg" => Element::Rg,
+            "cn" => Element::Cn,
             "nh" => Element::Nh,
             "fl" => Element::Fl,
             "mc" => Element::Mc,
@@ -179,6 +179,56 @@ impl From<&str> for Element {
     }
 }
 
+/// Elements are represented by their symbol in human-readable formats like JSON and by their
+/// atomic number in binary formats like bincode.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Element {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(self.symbol())
+        } else {
+            serializer.serialize_u8(self.number())
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Element {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ElementVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ElementVisitor {
+            type Value = Element;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("an element symbol or atomic number")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, symbol: &str) -> Result<Element, E> {
+                match Element::from(symbol) {
+                    Element::Dummy if !symbol.eq_ignore_ascii_case("x") => {
+                        Err(E::custom(format!("{} is not an element", symbol)))
+                    }
+                    element => Ok(element),
+                }
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, number: u64) -> Result<Element, E> {
+                match u8::try_from(number) {
+                    Ok(number) if number < Element::count() => Ok(Element::from(number)),
+                    _ => Err(E::custom(format!("{} is not an atomic number", number))),
+                }
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(ElementVisitor)
+        } else {
+            deserializer.deserialize_u8(ElementVisitor)
+        }
+    }
+}
+
 impl From<u8> for Element {
     fn from(number: u8) -> Self {
         match number {
@@ -802,3 +852,20 @@ impl Element {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::elements::Element;
+
+    #[test]
+    fn test_symbol_round_trip() {
+        for number in 0..Element::count() {
+            let element = Element::from(number);
+            assert_eq!(Element::from(element.symbol()), element);
+            assert_eq!(
+                Element::from(element.symbol().to_uppercase().as_str()),
+                element
+            );
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::elements::Element;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Errors that can occur while loading, converting or representing a basis set.
+#[derive(Debug)]
+pub enum AtoError {
+    /// The requested basis set is not known. Similar names are suggested, if there are any.
+    UnknownBasis {
+        name: String,
+        suggestions: Vec<String>,
+    },
+    /// The requested version of the basis set is not available.
+    UnknownVersion { basis: String, version: String },
+    /// The basis set does not contain any functions for the element.
+    MissingElement { basis: String, element: Element },
+    /// The basis set has no auxiliary basis set for the role. The roles for which auxiliary
+    /// basis sets exist are listed.
+    MissingAuxiliary {
+        basis: String,
+        role: String,
+        roles: Vec<String>,
+    },
+    /// A value of the basis set data could not be parsed.
+    Parse(String),
+    /// Reading or writing the local data failed.
+    Io(io::Error),
+    /// The basis set data could not be downloaded.
+    Download(String),
+    /// The basis set contains functions of a type that is not supported.
+    UnsupportedFunctionType(String),
+    /// A function was called with an argument that is not valid.
+    InvalidArgument(String),
+}
+
+impl fmt::Display for AtoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtoError::UnknownBasis { name, suggestions } => {
+                write!(f, "The basis set {} could not be found", name)?;
+                if !suggestions.is_empty() {
+                    write!(f, ". Did you mean {}?", suggestions.join(", "))?;
+                }
+                Ok(())
+            }
+            AtoError::UnknownVersion { basis, version } => write!(
+                f,
+                "The version {} of the basis set {} is not available",
+                version, basis
+            ),
+            AtoError::MissingElement { basis, element } => write!(
+                f,
+                "The basis set {} does not contain the element {}",
+                basis,
+                element.symbol()
+            ),
+            AtoError::MissingAuxiliary { basis, role, roles } => {
+                write!(
+                    f,
+                    "The basis set {} has no auxiliary basis set for the role {}",
+                    basis, role
+                )?;
+                if !roles.is_empty() {
+                    write!(f, ". Available roles: {}", roles.join(", "))?;
+                }
+                Ok(())
+            }
+            AtoError::Parse(msg) => write!(f, "Could not parse the basis set data: {}", msg),
+            AtoError::Io(err) => write!(f, "Could not access the basis set data: {}", err),
+            AtoError::Download(msg) => write!(f, "Could not download the basis set data: {}", msg),
+            AtoError::UnsupportedFunctionType(kind) => {
+                write!(f, "The function type {} is not supported", kind)
+            }
+            AtoError::InvalidArgument(msg) => write!(f, "Invalid argument: {}", msg),
+        }
+    }
+}
+
+impl Error for AtoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AtoError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AtoError {
+    fn from(err: io::Error) -> Self {
+        AtoError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for AtoError {
+    fn from(err: serde_json::Error) -> Self {
+        AtoError::Parse(err.to_string())
+    }
+}
+
+impl From<bincode::Error> for AtoError {
+    fn from(err: bincode::Error) -> Self {
+        AtoError::Parse(err.to_string())
+    }
+}
+
+impl From<reqwest::Error> for AtoError {
+    fn from(err: reqwest::Error) -> Self {
+        AtoError::Download(err.to_string())
+    }
+}
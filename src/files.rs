@@ -1,69 +1,565 @@
 use crate::bse::http::BasisSetExchange;
-use anyhow::{Context, Result};
-use std::path::PathBuf;
-use std::{env, fs};
+use crate::manifest::{Manifest, ManifestEntry};
+use anyhow::{bail, Context, Result};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::{env, fs, process, thread};
+use tokio::runtime::{self, Handle, Runtime};
 
 pub const ATO_ENV_VAR: &str = "ATO_DATA_PATH";
 pub const DEFAULT_DATA_PATH: &str = ".ato_rs/data/";
 pub const BASIS_SUBDIRECTORY: &str = "basis_sets/";
 pub const BINCODE_BASIS_DICT: &str = "basis_set_dict.bc";
 pub const JSON_BASIS_DICT: &str = "basis_set_dict.json";
+pub const JSON_METADATA: &str = "metadata.json";
+/// Directory for basis sets in the BSE JSON format that are not part of the BSE.
+pub const CUSTOM_SUBDIRECTORY: &str = "custom/";
+/// Directory for the bibliographic records of the basis sets.
+pub const REFERENCES_SUBDIRECTORY: &str = "references/";
+/// Lock file of the processes that write to the data directory, see [DataLock].
+pub const LOCK_FILE: &str = ".lock";
+
+/// Exclusive lock of the data directory, which is released when it is dropped.
+///
+/// The data directory may be shared by many processes, e.g. the jobs of a cluster. All
+/// functions of this module that download data hold this lock, so that only one process
+/// downloads while the others wait and find the data afterwards. Readers do not need the
+/// lock, as every file is replaced atomically, see [write_atomic].
+#[derive(Debug)]
+pub struct DataLock {
+    _file: fs::File,
+}
+
+impl DataLock {
+    /// Wait until no other process or thread holds the lock of the data directory.
+    pub fn acquire() -> Result<Self> {
+        let path = data_path(LOCK_FILE)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Could not create data dir")?;
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .context("Could not open the lock file")?;
+        file.lock().context("Could not lock the data directory")?;
+        Ok(Self { _file: file })
+    }
+
+    /// Async variant of [DataLock::acquire], which waits on a blocking thread of the runtime.
+    pub async fn acquire_async() -> Result<Self> {
+        tokio::task::spawn_blocking(Self::acquire)
+            .await
+            .context("Could not wait for the lock of the data directory")?
+    }
+}
 
 pub enum BasisSetData {
     Downloaded,
     ExistsAlready,
 }
 
-#[tokio::main]
-pub async fn update_data() -> Result<()> {
-    if let Ok(BasisSetData::ExistsAlready) = ensure_data_exist() {
-        BasisSetExchange::download_metadata().await?;
-        BasisSetExchange::download_basis_sets().await?;
+/// Result of a download of several basis sets.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DownloadSummary {
+    /// BSE ids of the basis sets that were downloaded.
+    pub downloaded: Vec<String>,
+    /// BSE ids of the basis sets that could not be downloaded, together with the error.
+    pub failed: Vec<(String, String)>,
+    /// Basis sets that were replaced by another version.
+    pub changes: Vec<BasisSetChange>,
+}
+
+/// A basis set that was replaced by another version from the BSE.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasisSetChange {
+    pub id: String,
+    pub old_version: String,
+    pub new_version: String,
+    pub revision_date: String,
+    /// Description of the changes of the new version, as given by the BSE.
+    pub revision_description: String,
+}
+
+impl DownloadSummary {
+    /// True if no download failed.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
     }
-    Ok(())
 }
 
-#[tokio::main]
-pub async fn ensure_data_exist() -> Result<BasisSetData> {
+/// Update the local data from the default endpoint, see [update_data_from].
+pub fn update_data() -> Result<DownloadSummary> {
+    update_data_from(&BasisSetExchange::from_env()?)
+}
+
+/// Async variant of [update_data], for use inside of a Tokio runtime.
+pub async fn update_data_async() -> Result<DownloadSummary> {
+    update_data_from_async(&BasisSetExchange::from_env()?).await
+}
+
+/// Update the local data from a configured [BasisSetExchange].
+///
+/// The metadata is downloaded again and only the local basis sets whose latest version on the
+/// BSE differs from the version on disk are downloaded. The returned summary lists these
+/// changes together with their revision description.
+pub fn update_data_from(bse: &BasisSetExchange) -> Result<DownloadSummary> {
+    block_on(update_data_from_async(bse))
+}
+
+/// Async variant of [update_data_from].
+pub async fn update_data_from_async(bse: &BasisSetExchange) -> Result<DownloadSummary> {
+    let _lock = DataLock::acquire_async().await?;
+    create_data_dir()?;
+    let metadata = bse.download_metadata().await?;
+    bse.download_outdated_basis_sets(&metadata).await
+}
+
+/// Prefetch the complete Basis Set Exchange mirror (about 400 MB).
+///
+/// This is not required for normal use, as [ensure_basis_exist] downloads single basis sets on
+/// demand. It is meant for machines that will lose their internet connection later on.
+/// Only basis sets that are missing or incomplete are downloaded, so an interrupted or partly
+/// failed run is repaired by calling this function again. The returned summary lists the
+/// basis sets that could not be downloaded.
+pub fn ensure_data_exist() -> Result<DownloadSummary> {
+    ensure_data_exist_from(&BasisSetExchange::from_env()?)
+}
+
+/// Async variant of [ensure_data_exist], for use inside of a Tokio runtime.
+pub async fn ensure_data_exist_async() -> Result<DownloadSummary> {
+    ensure_data_exist_from_async(&BasisSetExchange::from_env()?).await
+}
+
+/// Prefetch the complete mirror from a configured [BasisSetExchange], see [ensure_data_exist].
+pub fn ensure_data_exist_from(bse: &BasisSetExchange) -> Result<DownloadSummary> {
     if data_exists()? {
-        return Ok(BasisSetData::ExistsAlready);
+        return Ok(DownloadSummary::default());
     }
+    block_on(ensure_data_exist_from_async(bse))
+}
+
+/// Async variant of [ensure_data_exist_from].
+pub async fn ensure_data_exist_from_async(bse: &BasisSetExchange) -> Result<DownloadSummary> {
+    let _lock = DataLock::acquire_async().await?;
     create_data_dir()?;
-    BasisSetExchange::download_metadata().await?;
-    BasisSetExchange::download_basis_sets().await?;
+    if !metadata_exists()? {
+        bse.download_metadata().await?;
+    }
+    bse.download_missing_basis_sets().await
+}
+
+/// Download the dictionary of basis set names, if it does not exist yet.
+pub fn ensure_metadata_exist(bse: &BasisSetExchange) -> Result<BasisSetData> {
+    ensure_blocking(metadata_exists()?, ensure_metadata_exist_async(bse))
+}
+
+/// Async variant of [ensure_metadata_exist].
+pub async fn ensure_metadata_exist_async(bse: &BasisSetExchange) -> Result<BasisSetData> {
+    download_locked(metadata_exists, async {
+        create_data_dir()?;
+        bse.download_metadata().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Download the complete metadata of all basis sets, if it does not exist yet.
+///
+/// Data directories of older versions of this crate only contain the dictionary of names.
+pub fn ensure_catalogue_exist(bse: &BasisSetExchange) -> Result<BasisSetData> {
+    ensure_blocking(catalogue_exists()?, ensure_catalogue_exist_async(bse))
+}
+
+/// Async variant of [ensure_catalogue_exist].
+pub async fn ensure_catalogue_exist_async(bse: &BasisSetExchange) -> Result<BasisSetData> {
+    download_locked(catalogue_exists, async {
+        create_data_dir()?;
+        bse.download_metadata().await?;
+        Ok(())
+    })
+    .await
+}
+
+/// Download a single basis set by its BSE id, if it does not exist yet.
+pub fn ensure_basis_exist(bse: &BasisSetExchange, id: &str) -> Result<BasisSetData> {
+    ensure_blocking(basis_root(id)?.is_some(), ensure_basis_exist_async(bse, id))
+}
+
+/// Async variant of [ensure_basis_exist].
+pub async fn ensure_basis_exist_async(bse: &BasisSetExchange, id: &str) -> Result<BasisSetData> {
+    let exists = || Ok(basis_root(id)?.is_some());
+    download_locked(exists, async {
+        create_data_dir()?;
+        bse.download_basis_set(id).await
+    })
+    .await
+}
+
+/// Download a specific version of a basis set, if it does not exist yet.
+///
+/// Pinned versions are stored next to the latest version, see [versioned_id].
+pub fn ensure_basis_version_exist(
+    bse: &BasisSetExchange,
+    id: &str,
+    version: &str,
+) -> Result<BasisSetData> {
+    let exists = basis_root(&versioned_id(id, version))?.is_some();
+    ensure_blocking(exists, ensure_basis_version_exist_async(bse, id, version))
+}
+
+/// Async variant of [ensure_basis_version_exist].
+pub async fn ensure_basis_version_exist_async(
+    bse: &BasisSetExchange,
+    id: &str,
+    version: &str,
+) -> Result<BasisSetData> {
+    let exists = || Ok(basis_root(&versioned_id(id, version))?.is_some());
+    download_locked(exists, async {
+        create_data_dir()?;
+        bse.download_basis_set_version(id, version).await
+    })
+    .await
+}
+
+/// Download the bibliographic records of a basis set by its BSE id, if they do not exist yet.
+pub fn ensure_references_exist(bse: &BasisSetExchange, id: &str) -> Result<BasisSetData> {
+    ensure_blocking(
+        references_exist(id)?,
+        ensure_references_exist_async(bse, id),
+    )
+}
+
+/// Async variant of [ensure_references_exist].
+pub async fn ensure_references_exist_async(
+    bse: &BasisSetExchange,
+    id: &str,
+) -> Result<BasisSetData> {
+    download_locked(|| references_exist(id), async {
+        fs::create_dir_all(data_path(REFERENCES_SUBDIRECTORY)?)
+            .context("Could not create references dir")?;
+        bse.download_references(id).await
+    })
+    .await
+}
+
+/// Download the bibliographic records of a basis set again, e.g. when the cached ones lack
+/// a reference of a newer version of the basis set.
+pub fn update_references(bse: &BasisSetExchange, id: &str) -> Result<()> {
+    block_on(update_references_async(bse, id))
+}
+
+/// Async variant of [update_references].
+pub async fn update_references_async(bse: &BasisSetExchange, id: &str) -> Result<()> {
+    let _lock = DataLock::acquire_async().await?;
+    fs::create_dir_all(data_path(REFERENCES_SUBDIRECTORY)?)
+        .context("Could not create references dir")?;
+    bse.download_references(id).await
+}
+
+fn catalogue_exists() -> Result<bool> {
+    Ok(find_data_path(JSON_METADATA)?.is_some())
+}
+
+fn references_exist(id: &str) -> Result<bool> {
+    Ok(find_data_path(&references_suffix(id))?.is_some())
+}
+
+/// Run the download while holding the [DataLock], unless the data exists.
+///
+/// The check is repeated once the lock is held, as another process may have downloaded the
+/// data in the meantime.
+async fn download_locked<E, D>(exists: E, download: D) -> Result<BasisSetData>
+where
+    E: Fn() -> Result<bool>,
+    D: Future<Output = Result<()>>,
+{
+    if exists()? {
+        return Ok(BasisSetData::ExistsAlready);
+    }
+    let _lock = DataLock::acquire_async().await?;
+    if exists()? {
+        return Ok(BasisSetData::ExistsAlready);
+    }
+    download.await?;
     Ok(BasisSetData::Downloaded)
 }
 
-pub fn create_data_dir() -> Result<()> {
-    let mut path = data_path("")?;
-    if let Some(parent) = path.parent() {
-        if !parent.exists() {
-            fs::create_dir(parent).context("Could not create parent dir")?
-        }
+/// Run the async variant of a blocking function, unless the data exists already. Data that
+/// exists is therefore used without starting a runtime.
+fn ensure_blocking<F>(exists: bool, future: F) -> Result<BasisSetData>
+where
+    F: Future<Output = Result<BasisSetData>> + Send,
+{
+    if exists {
+        return Ok(BasisSetData::ExistsAlready);
+    }
+    block_on(future)
+}
+
+/// Run a future of the blocking API to completion.
+///
+/// All blocking functions share one runtime, which is only started when something has to be
+/// downloaded. Runtimes can not be nested, so the future is run on a separate thread if the
+/// blocking API is called from inside of a runtime, e.g. from an async service.
+pub(crate) fn block_on<T, F>(future: F) -> Result<T>
+where
+    T: Send,
+    F: Future<Output = Result<T>> + Send,
+{
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    let run = || -> Result<T> {
+        let runtime = match RUNTIME.get() {
+            Some(runtime) => runtime,
+            None => {
+                let runtime = runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .build()
+                    .context("Could not start the runtime")?;
+                RUNTIME.get_or_init(|| runtime)
+            }
+        };
+        runtime.block_on(future)
     };
-    fs::create_dir(&path).context("Could not create data dir")?;
-    path.push("basis_sets/");
-    fs::create_dir(&path).context("Could not create basis set dir")?;
+    if Handle::try_current().is_err() {
+        return run();
+    }
+    thread::scope(|scope| match scope.spawn(run).join() {
+        Ok(result) => result,
+        Err(panic) => std::panic::resume_unwind(panic),
+    })
+}
+
+pub fn create_data_dir() -> Result<()> {
+    let path = data_path(BASIS_SUBDIRECTORY)?;
+    fs::create_dir_all(&path).context("Could not create basis set dir")?;
     Ok(())
 }
 
+/// Check that the dictionary of names and all basis sets listed in it are complete.
 pub fn data_exists() -> Result<bool> {
-    let path = data_path(BASIS_SUBDIRECTORY)?;
-    if !path.exists() {
+    if !metadata_exists()? {
         return Ok(false);
     }
-    Ok(path.read_dir()?.next().is_some() && data_path(BINCODE_BASIS_DICT)?.exists())
+    let manifests = Manifest::read_all()?;
+    for id in BasisSetExchange::read_names()?.values() {
+        if !is_complete_in(&manifests, id)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
 }
 
-pub fn data_path(suffix: &str) -> Result<PathBuf> {
-    let mut path = match env::var(ATO_ENV_VAR) {
-        Ok(value) => PathBuf::from(value),
-        Err(_) => {
-            let mut home = dirs::home_dir().context("Could not resolve the home directory")?;
-            home.push(DEFAULT_DATA_PATH);
-            home
+pub fn metadata_exists() -> Result<bool> {
+    Ok(find_data_path(BINCODE_BASIS_DICT)?.is_some())
+}
+
+/// True if the basis set is complete in any of the manifests.
+pub(crate) fn is_complete_in(manifests: &[Manifest], id: &str) -> Result<bool> {
+    for manifest in manifests.iter() {
+        if manifest.is_complete(id)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Data root from which a basis set is read, given its BSE id.
+///
+/// A basis set may be complete in several roots, e.g. an outdated copy in a shared library and
+/// an updated one in the writable root. The root with the latest version is used then, or the
+/// first of them if the versions are equal, see [data_roots].
+pub fn basis_root(id: &str) -> Result<Option<PathBuf>> {
+    Ok(latest_entry(&Manifest::read_all()?, id)?.map(|(root, _)| root))
+}
+
+/// Data root and manifest entry of the latest complete version of a basis set in any of the
+/// manifests.
+pub(crate) fn latest_entry(
+    manifests: &[Manifest],
+    id: &str,
+) -> Result<Option<(PathBuf, ManifestEntry)>> {
+    let mut latest: Option<(PathBuf, ManifestEntry)> = None;
+    for manifest in manifests.iter() {
+        let entry = match manifest.basis_sets.get(id) {
+            Some(entry) if manifest.is_complete(id)? => entry,
+            _ => continue,
+        };
+        if latest
+            .as_ref()
+            .is_none_or(|(_, newest)| entry.is_newer_than(newest))
+        {
+            latest = Some((manifest.root()?, entry.clone()));
         }
+    }
+    Ok(latest)
+}
+
+/// Path of the JSON file of a basis set in the writable data root, given its BSE id.
+pub fn basis_path(id: &str) -> Result<PathBuf> {
+    data_path(&format!("{}{}.json", BASIS_SUBDIRECTORY, id))
+}
+
+/// Path of the cached bibliographic records of a basis set in the writable data root, given
+/// its BSE id.
+pub fn references_path(id: &str) -> Result<PathBuf> {
+    data_path(&references_suffix(id))
+}
+
+pub(crate) fn references_suffix(id: &str) -> String {
+    format!("{}{}.json", REFERENCES_SUBDIRECTORY, id)
+}
+
+/// Write a file atomically.
+///
+/// The data is written to a temporary file in the same directory, which is renamed afterwards.
+/// Readers will therefore either see the old or the new file, but never a partial one.
+pub fn write_atomic<P: AsRef<Path>>(path: P, data: &[u8]) -> Result<()> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let path = path.as_ref();
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .context("Invalid file name")?;
+    let tmp_path = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        file_name,
+        process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&tmp_path, data).context("Could not write temporary file")?;
+    if let Err(err) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(err).context("Could not move temporary file");
+    }
+    Ok(())
+}
+
+/// Identifier of the file of a pinned version of a basis set, e.g. `sto-3g_v0`.
+pub fn versioned_id(id: &str, version: &str) -> String {
+    format!("{}_v{}", id, version)
+}
+
+/// Roots of the data directory in the order in which they are searched.
+///
+/// $ATO_DATA_PATH may contain several roots, separated like $PATH, e.g. a read-only library
+/// that is maintained for all users followed by a directory of the user:
+/// `ATO_DATA_PATH=/shared/ato:/home/user/.ato_rs/data`. Downloads are written to the first root
+/// that is writable, see [data_root]. The metadata is read from the writable root before the
+/// others, so that an update of the user is not hidden by an older shared copy, see
+/// [find_data_path]. Basis sets are read from the root with the latest version, see
+/// [basis_root].
+pub fn data_roots() -> Result<Vec<PathBuf>> {
+    let roots: Vec<PathBuf> = match env::var_os(ATO_ENV_VAR) {
+        Some(value) => env::split_paths(&value)
+            .filter(|root| !root.as_os_str().is_empty())
+            .collect(),
+        None => Vec::new(),
     };
+    if !roots.is_empty() {
+        return Ok(roots);
+    }
+    let mut home = dirs::home_dir().context("Could not resolve the home directory")?;
+    home.push(DEFAULT_DATA_PATH);
+    Ok(vec![home])
+}
+
+/// The data root to which downloads are written, which is the first writable one.
+///
+/// A single root is always used, even if it does not exist yet.
+pub fn data_root() -> Result<PathBuf> {
+    match writable_root(&data_roots()?) {
+        Some(root) => Ok(root),
+        None => bail!("None of the data roots in ${} is writable", ATO_ENV_VAR),
+    }
+}
+
+/// Path of a file in the writable data root.
+pub fn data_path(suffix: &str) -> Result<PathBuf> {
+    let mut path = data_root()?;
     path.push(suffix);
     Ok(path)
 }
+
+/// Path of a file in the writable data root, or else in the first other root that contains it.
+///
+/// All roots may be read-only, e.g. on the nodes of a cluster, which only use a shared library.
+pub fn find_data_path(suffix: &str) -> Result<Option<PathBuf>> {
+    let mut roots = data_roots()?;
+    let writable = writable_root(&roots);
+    // The sort is stable, so the other roots keep their order.
+    roots.sort_by_key(|root| Some(root) != writable.as_ref());
+    Ok(roots
+        .into_iter()
+        .map(|root| root.join(suffix))
+        .find(|path| path.exists()))
+}
+
+/// The first writable one of the roots, or `None` if none of them is writable.
+///
+/// The roots are only checked once per process, as the result is cached until $ATO_DATA_PATH
+/// changes.
+fn writable_root(roots: &[PathBuf]) -> Option<PathBuf> {
+    static WRITABLE: Mutex<Option<(Vec<PathBuf>, Option<PathBuf>)>> = Mutex::new(None);
+    if roots.len() == 1 {
+        return Some(roots[0].clone());
+    }
+    let mut cached = WRITABLE.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some((cached_roots, root)) = cached.as_ref() {
+        if cached_roots == roots {
+            return root.clone();
+        }
+    }
+    let root = roots.iter().find(|root| is_writable(root)).cloned();
+    *cached = Some((roots.to_vec(), root.clone()));
+    root
+}
+
+/// Check that a file can be created in the root and that the root is not marked read-only,
+/// which also protects a shared library from its administrator. A root that does not exist yet
+/// is not created, instead the closest directory above it that exists has to be writable.
+fn is_writable(root: &Path) -> bool {
+    let existing = root
+        .ancestors()
+        .map(|path| {
+            if path.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                path
+            }
+        })
+        .find(|path| path.exists());
+    let directory = match existing {
+        Some(directory) if directory.is_dir() => directory,
+        _ => return false,
+    };
+    if fs::metadata(directory).map_or(true, |metadata| metadata.permissions().readonly()) {
+        return false;
+    }
+    let probe = directory.join(format!(".write_test.{}", process::id()));
+    match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::files::basis_path;
+
+    #[test]
+    fn test_basis_path() {
+        let path = basis_path("sto-3g").unwrap();
+        assert!(path.ends_with("basis_sets/sto-3g.json"));
+    }
+}
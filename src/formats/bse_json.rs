@@ -0,0 +1,351 @@
+use crate::basis_function::{BasisFunction, OriginalValues};
+use crate::basis_set::BasisSet;
+use crate::bse::basisset_json::{
+    InputAuxiliary, InputData, InputElement, InputPotential, InputSchema, InputShell,
+};
+use crate::ecp::EcpProjector;
+use crate::elements::Element;
+use crate::error::AtoError;
+use crate::formats::{number, selected_elements, BasisFormat};
+use crate::slater_function::SlaterFunction;
+use std::collections::BTreeSet;
+
+/// The JSON format of the BSE (`molssi_bse_schema`), which is read by [InputData].
+///
+/// Values that were not changed since the basis set was read are written exactly as they
+/// were given, and so are the regions, function types and references. Consecutive functions
+/// with the same exponents are written as one shell again, e.g. the SP shells of Pople basis
+/// sets. A file that is written in this format can be placed in the `custom` subdirectory of
+/// the data directory, see [crate::files::CUSTOM_SUBDIRECTORY], to load it with
+/// [BasisSet::new].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BseJson;
+
+impl BasisFormat for BseJson {
+    fn write_elements(&self, basis: &BasisSet, elements: &[Element]) -> Result<String, AtoError> {
+        Ok(serde_json::to_string_pretty(&input_data(basis, elements)?)?)
+    }
+}
+
+/// Convert the given elements of a basis set into the BSE data structure.
+pub fn input_data(basis: &BasisSet, elements: &[Element]) -> Result<InputData, AtoError> {
+    let elements = selected_elements(basis, elements)?;
+    let mut function_types: BTreeSet<String> = basis.function_types.iter().cloned().collect();
+    let mut data = InputData {
+        molssi_bse_schema: InputSchema::default(),
+        name: basis.name.clone(),
+        elements: Default::default(),
+        description: basis.description.clone(),
+        revision_description: basis.revision_description.clone(),
+        revision_date: basis.revision_date.clone(),
+        version: basis.version.clone(),
+        function_types: Vec::new(),
+        names: basis.names.clone(),
+        tags: basis.tags.clone(),
+        family: basis.family.clone(),
+        role: basis.role.clone(),
+        auxiliaries: basis
+            .auxiliaries
+            .iter()
+            .map(|(role, names)| {
+                let auxiliary = match names.as_slice() {
+                    [name] => InputAuxiliary::One(name.clone()),
+                    _ => InputAuxiliary::Many(names.clone()),
+                };
+                (role.clone(), auxiliary)
+            })
+            .collect(),
+    };
+
+    for element in elements {
+        let mut electron_shells: Vec<InputShell> = basis
+            .basis_functions
+            .get(&element)
+            .map(|functions| shells(functions))
+            .unwrap_or_default();
+        if let Some(functions) = basis.slater_functions.get(&element) {
+            electron_shells.extend(slater_shells(functions));
+        }
+        let ecp = basis.ecps.get(&element);
+        let ecp_potentials: Vec<InputPotential> = ecp
+            .map(|ecp| ecp.projectors.iter().map(potential).collect())
+            .unwrap_or_default();
+        function_types.extend(electron_shells.iter().map(|s| s.function_type.clone()));
+        function_types.extend(ecp_potentials.iter().map(|p| p.ecp_type.clone()));
+        data.elements.insert(
+            element.number_usize(),
+            InputElement {
+                electron_shells,
+                references: basis.references.get(&element).cloned().unwrap_or_default(),
+                ecp_electrons: ecp.map(|ecp| ecp.core_electrons),
+                ecp_potentials,
+            },
+        );
+    }
+    data.function_types = function_types.into_iter().collect();
+    Ok(data)
+}
+
+/// The strings of the values. Unchanged values keep their original representation.
+fn strings(values: &[f64], original: Option<&Vec<String>>) -> Vec<String> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, x)| match original.and_then(|o| o.get(i)) {
+            Some(s) if s.parse::<f64>().ok() == Some(*x) => s.clone(),
+            _ => number(*x),
+        })
+        .collect()
+}
+
+/// The BSE function type of a shell. Shells with s and p functions are `gto` on the BSE,
+/// as there is no difference between spherical and Cartesian functions.
+fn function_type(function: &BasisFunction) -> String {
+    let higher = function.l as u8 >= 2;
+    match &function.original {
+        Some(OriginalValues {
+            kind,
+            function_type: Some(read_as),
+            ..
+        }) if *read_as == function.function_type && !(higher && kind == "gto") => kind.clone(),
+        _ if !higher => "gto".to_owned(),
+        _ => function.function_type.bse_name().to_owned(),
+    }
+}
+
+/// Whether a function can be added to a shell of the given functions.
+///
+/// A shell is either a general contraction of one angular momentum or has one contraction for
+/// each of several angular momenta, like the SP shells.
+fn fits(shell: &[&BasisFunction], function: &BasisFunction) -> bool {
+    let first = shell[0];
+    if first.exponents != function.exponents
+        || first.region != function.region
+        || function_type(first) != function_type(function)
+    {
+        return false;
+    }
+    let general = shell.iter().all(|f| f.l == function.l);
+    let ascending = shell.windows(2).all(|w| (w[0].l as u8) < (w[1].l as u8))
+        && (shell[shell.len() - 1].l as u8) < (function.l as u8);
+    general || ascending
+}
+
+fn shells(functions: &[BasisFunction]) -> Vec<InputShell> {
+    let mut groups: Vec<Vec<&BasisFunction>> = Vec::new();
+    for function in functions.iter() {
+        match groups.last_mut() {
+            Some(shell) if fits(shell, function) => shell.push(function),
+            _ => groups.push(vec![function]),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|shell| {
+            let first = shell[0];
+            let mut angular_momentum: Vec<usize> = shell.iter().map(|f| f.l as usize).collect();
+            angular_momentum.dedup();
+            InputShell {
+                function_type: function_type(first),
+                region: first.region.clone(),
+                angular_momentum,
+                exponents: strings(
+                    &first.exponents,
+                    first.original.as_ref().map(|o| &o.exponents),
+                ),
+                coefficients: shell
+                    .iter()
+                    .map(|f| {
+                        strings(
+                            &f.coefficients,
+                            f.original.as_ref().map(|o| &o.coefficients),
+                        )
+                    })
+                    .collect(),
+                r_exponents: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Slater-type shells, where consecutive functions with the same primitives are written as
+/// one general contraction.
+fn slater_shells(functions: &[SlaterFunction]) -> Vec<InputShell> {
+    let mut groups: Vec<Vec<&SlaterFunction>> = Vec::new();
+    for function in functions.iter() {
+        match groups.last_mut() {
+            Some(shell)
+                if shell[0].l == function.l
+                    && shell[0].exponents == function.exponents
+                    && shell[0].principal_numbers == function.principal_numbers
+                    && shell[0].region == function.region =>
+            {
+                shell.push(function)
+            }
+            _ => groups.push(vec![function]),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|shell| {
+            let first = shell[0];
+            InputShell {
+                function_type: "sto".to_owned(),
+                region: first.region.clone(),
+                angular_momentum: vec![first.l as usize],
+                exponents: strings(
+                    &first.exponents,
+                    first.original.as_ref().map(|o| &o.exponents),
+                ),
+                coefficients: shell
+                    .iter()
+                    .map(|f| {
+                        strings(
+                            &f.coefficients,
+                            f.original.as_ref().map(|o| &o.coefficients),
+                        )
+                    })
+                    .collect(),
+                r_exponents: first.principal_numbers.iter().map(|n| *n as i32).collect(),
+            }
+        })
+        .collect()
+}
+
+fn potential(projector: &EcpProjector) -> InputPotential {
+    InputPotential {
+        angular_momentum: vec![projector.l as usize],
+        r_exponents: projector.r_exponents.clone(),
+        gaussian_exponents: strings(
+            &projector.exponents,
+            projector.original.as_ref().map(|o| &o.exponents),
+        ),
+        coefficients: vec![strings(
+            &projector.coefficients,
+            projector.original.as_ref().map(|o| &o.coefficients),
+        )],
+        ecp_type: projector.kind.bse_name().to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::angular_momentum::AngularMomentum;
+    use crate::basis_function::{BasisFunction, FunctionType};
+    use crate::basis_set::BasisSet;
+    use crate::bse::basisset_json::InputData;
+    use crate::elements::Element;
+    use crate::formats::bse_json::input_data;
+    use crate::formats::{gaussian94, BasisFormat, BseJson};
+    use std::convert::TryFrom;
+
+    /// Carbon of 6-31G* with an SP shell, a Cartesian d shell and a reference.
+    const CARBON_631G_ST: &str = r#"
+    {
+        "molssi_bse_schema": {"schema_type": "complete", "schema_version": "0.1"},
+        "revision_description": "Data from the Original Basis Set Exchange",
+        "revision_date": "2018-06-19",
+        "elements": {
+            "6": {
+                "electron_shells": [
+                    {
+                        "function_type": "gto",
+                        "region": "",
+                        "angular_momentum": [0, 1],
+                        "exponents": ["0.1687144782E+00"],
+                        "coefficients": [["0.1000000000E+01"], ["0.1000000000E+01"]]
+                    },
+                    {
+                        "function_type": "gto_cartesian",
+                        "region": "polarization",
+                        "angular_momentum": [2],
+                        "exponents": ["0.8000000E+00"],
+                        "coefficients": [["1.0000000"]]
+                    }
+                ],
+                "references": [{"reference_description": "Basis set", "reference_keys": ["hariharan1973a"]}]
+            }
+        },
+        "version": "1",
+        "function_types": ["gto", "gto_cartesian"],
+        "names": ["6-31G*", "6-31G(d)"],
+        "tags": [],
+        "family": "pople",
+        "description": "6-31G + polarization",
+        "role": "orbital",
+        "auxiliaries": {"jkfit": "def2-universal-jkfit"},
+        "name": "6-31G*"
+    }"#;
+
+    #[test]
+    fn test_lossless() {
+        let original: serde_json::Value = serde_json::from_str(CARBON_631G_ST).unwrap();
+        let data: InputData = serde_json::from_str(CARBON_631G_ST).unwrap();
+        let basis = BasisSet::try_from(data).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&BseJson.write(&basis).unwrap()).unwrap();
+        assert_eq!(written, original);
+    }
+
+    #[test]
+    fn test_modified() {
+        let data: InputData = serde_json::from_str(CARBON_631G_ST).unwrap();
+        let mut basis = BasisSet::try_from(data).unwrap();
+        let functions = basis.basis_functions.get_mut(&Element::C).unwrap();
+        functions[0].coefficients[0] = 0.5;
+        functions.push(BasisFunction::new(
+            AngularMomentum::S,
+            vec![0.04],
+            vec![1.0],
+            FunctionType::Spherical,
+        ));
+        let data = input_data(&basis, &[Element::C]).unwrap();
+        let shells = &data.elements[&6].electron_shells;
+        assert_eq!(shells.len(), 3);
+        assert_eq!(shells[0].exponents, vec!["0.1687144782E+00".to_string()]);
+        assert_eq!(shells[0].coefficients[0], vec!["0.5".to_string()]);
+        assert_eq!(
+            shells[0].coefficients[1],
+            vec!["0.1000000000E+01".to_string()]
+        );
+        assert_eq!(shells[2].function_type, "gto".to_string());
+
+        // The modified data is read again.
+        let again = BasisSet::try_from(data).unwrap();
+        assert_eq!(again.basis_functions[&Element::C].len(), 4);
+        assert_eq!(
+            again.basis_functions[&Element::C][2].function_type,
+            FunctionType::Cartesian
+        );
+    }
+
+    #[test]
+    fn test_override_function_type() {
+        let data: InputData = serde_json::from_str(CARBON_631G_ST).unwrap();
+        let basis = BasisSet::try_from(data)
+            .unwrap()
+            .with_function_type(FunctionType::Spherical);
+        let data = input_data(&basis, &[Element::C]).unwrap();
+        assert_eq!(
+            data.elements[&6].electron_shells[1].function_type,
+            "gto_spherical"
+        );
+        let again = BasisSet::try_from(data).unwrap();
+        assert_eq!(
+            again.basis_functions[&Element::C][2].function_type,
+            FunctionType::Spherical
+        );
+    }
+
+    #[test]
+    fn test_from_gaussian94() {
+        let basis = gaussian94::read("mini", "H 0\nSP 1 1.00\n 0.5 1.0 2.0\n****\n").unwrap();
+        let data = input_data(&basis, &[Element::H]).unwrap();
+        let shell = &data.elements[&1].electron_shells[0];
+        assert_eq!(shell.angular_momentum, vec![0, 1]);
+        assert_eq!(
+            shell.coefficients,
+            vec![vec!["1.0".to_string()], vec!["2.0".to_string()]]
+        );
+    }
+}
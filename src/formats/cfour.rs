@@ -0,0 +1,163 @@
+use crate::angular_momentum::AngularMomentum;
+use crate::basis_function::BasisFunction;
+use crate::basis_set::BasisSet;
+use crate::elements::Element;
+use crate::error::AtoError;
+use crate::formats::{
+    ecp_blocks, ecp_terms, functions, gaussian_elements, label, number, BasisFormat,
+};
+
+/// The `GENBAS` format of CFOUR, followed by the `ECPDATA` entries of the ECPs.
+///
+/// CFOUR expects a single general contraction per angular momentum. All contractions of an
+/// angular momentum are therefore merged into one block, in which the coefficients of the
+/// exponents that are not part of a contraction are zero.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cfour;
+
+/// All exponents of an angular momentum and one column of coefficients per contraction.
+struct Block {
+    l: AngularMomentum,
+    exponents: Vec<f64>,
+    columns: Vec<Vec<f64>>,
+}
+
+fn blocks(functions: &[BasisFunction]) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    for l in 0..=10u8 {
+        let shells: Vec<&BasisFunction> = functions.iter().filter(|f| f.l as u8 == l).collect();
+        if shells.is_empty() {
+            continue;
+        }
+        let mut exponents: Vec<f64> = Vec::new();
+        for e in shells.iter().flat_map(|f| f.exponents.iter()) {
+            if !exponents.contains(e) {
+                exponents.push(*e);
+            }
+        }
+        let columns = shells
+            .iter()
+            .map(|f| {
+                exponents
+                    .iter()
+                    .map(|e| {
+                        f.exponents
+                            .iter()
+                            .position(|x| x == e)
+                            .map_or(0.0, |i| f.coefficients[i])
+                    })
+                    .collect()
+            })
+            .collect();
+        blocks.push(Block {
+            l: shells[0].l,
+            exponents,
+            columns,
+        });
+    }
+    blocks
+}
+
+/// Write the numbers of a line, `n` at a time.
+fn rows(values: &[String], n: usize) -> String {
+    values
+        .chunks(n)
+        .map(|chunk| format!("{}\n", chunk.join(" ")))
+        .collect()
+}
+
+impl BasisFormat for Cfour {
+    fn write_elements(&self, basis: &BasisSet, elements: &[Element]) -> Result<String, AtoError> {
+        let elements = gaussian_elements(basis, elements)?;
+        let name = basis.name.to_uppercase();
+        let mut txt = String::new();
+        for element in elements.iter() {
+            let blocks = blocks(functions(basis, element));
+            if blocks.is_empty() {
+                continue;
+            }
+            txt += &format!(
+                "{}:{}\n{}\n\n",
+                element.symbol().to_uppercase(),
+                name,
+                basis.description
+            );
+            txt += &format!("{:>3}\n", blocks.len());
+            let header = |value: fn(&Block) -> usize| -> String {
+                blocks.iter().map(|b| format!("{:>5}", value(b))).collect()
+            };
+            txt += &format!("{}\n", header(|b| b.l as usize));
+            txt += &format!("{}\n", header(|b| b.columns.len()));
+            txt += &format!("{}\n\n", header(|b| b.exponents.len()));
+            for block in blocks.iter() {
+                let exponents: Vec<String> = block.exponents.iter().map(|e| number(*e)).collect();
+                txt += &rows(&exponents, 5);
+                txt += "\n";
+                for i in 0..block.exponents.len() {
+                    let row: Vec<String> = block.columns.iter().map(|c| number(c[i])).collect();
+                    txt += &format!("{}\n", row.join(" "));
+                }
+                txt += "\n";
+            }
+        }
+
+        for element in elements.iter() {
+            let ecp = match basis.ecps.get(element) {
+                Some(ecp) => ecp,
+                None => continue,
+            };
+            let blocks = ecp_blocks(ecp)?;
+            let max_l = match blocks.first() {
+                Some((l, _)) => *l,
+                None => continue,
+            };
+            txt += &format!("{}:{}\n#\n*\n", element.symbol().to_uppercase(), name);
+            txt += &format!(
+                "    NCORE = {}    LMAX = {}\n",
+                ecp.core_electrons, max_l as u8
+            );
+            for (i, (l, projectors)) in blocks.into_iter().enumerate() {
+                if i == 0 {
+                    txt += &format!("{}\n", label(l));
+                } else {
+                    txt += &format!("{}-{}\n", label(l), label(max_l));
+                }
+                // The powers of r follow the Turbomole convention.
+                for (r, e, c) in ecp_terms(&projectors) {
+                    txt += &format!("  {:>20} {:>3} {:>20}\n", number(c), r - 2, number(e));
+                }
+            }
+            txt += "*\n";
+        }
+        Ok(txt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::elements::Element;
+    use crate::formats::cfour::blocks;
+    use crate::formats::tests::basis;
+    use crate::formats::{BasisFormat, Cfour};
+
+    #[test]
+    fn test_blocks() {
+        let basis = basis();
+        // The s shell and the s part of the SP shell of carbon have different exponents.
+        let blocks = blocks(&basis.basis_functions[&Element::C]);
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].exponents, vec![71.616837, 2.9412494]);
+        assert_eq!(
+            blocks[0].columns,
+            vec![vec![0.15432897, 0.0], vec![0.0, -0.09996723]]
+        );
+    }
+
+    #[test]
+    fn test_cfour() {
+        let txt = Cfour.write(&basis()).unwrap();
+        assert!(txt.starts_with("H:TEST\n\n\n  2\n    0    1\n    2    1\n    2    1\n\n"));
+        assert!(txt.contains("13.01 1.962\n\n0.019685 0.0\n0.137977 1.0\n\n"));
+        assert!(txt.contains("I:TEST\n#\n*\n    NCORE = 28    LMAX = 2\nd\n"));
+    }
+}
@@ -0,0 +1,583 @@
+//! The Gaussian94 (`.gbs`) format, as it is used by Gaussian and exported by the BSE.
+//!
+//! Every element starts with a line of its symbol and `0`. The shells follow with their
+//! angular momentum, number of primitives and scale factor, and the block ends with `****`.
+//! Many files, e.g. the exports of the EMSL library, also start with `****`. ECPs are appended
+//! in separate blocks after all basis functions.
+
+use crate::angular_momentum::AngularMomentum;
+use crate::basis_function::{BasisFunction, FunctionType};
+use crate::basis_set::BasisSet;
+use crate::ecp::{EcpProjector, EcpType, EffectiveCorePotential};
+use crate::elements::Element;
+use crate::error::AtoError;
+use crate::formats::{ecp_blocks, ecp_terms, from_label, gaussian_elements, label, BasisFormat};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Parse a basis set in the Gaussian94 format.
+///
+/// The exponents are multiplied by the square of the scale factor of their shell and `SP`
+/// shells are split into an s and a p function with the same exponents. The format does not
+/// specify whether the shells are spherical or Cartesian, so they are spherical like in
+/// Gaussian, see [BasisSet::set_function_type].
+pub fn read(name: &str, text: &str) -> Result<BasisSet, AtoError> {
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('!'))
+        .peekable();
+    let mut basis_functions: HashMap<Element, Vec<BasisFunction>> = HashMap::new();
+    let mut ecps: HashMap<Element, EffectiveCorePotential> = HashMap::new();
+
+    while let Some(line) = lines.next() {
+        // Separators before the first block or between blocks.
+        if line.starts_with("****") {
+            continue;
+        }
+        let element = parse_element(line)?;
+        if lines.peek().is_some_and(|next| is_ecp_header(next)) {
+            ecps.insert(element, read_ecp(&mut lines)?);
+            continue;
+        }
+        let functions = basis_functions.entry(element).or_default();
+        loop {
+            let line = lines
+                .next()
+                .ok_or_else(|| parse_error("the block of an element does not end with ****"))?;
+            if line.starts_with("****") {
+                break;
+            }
+            functions.append(&mut read_shell(line, &mut lines)?);
+        }
+    }
+
+    Ok(BasisSet {
+        id: name.to_lowercase(),
+        name: name.to_owned(),
+        basis_functions,
+        ecps,
+        ..BasisSet::default()
+    })
+}
+
+/// Write a basis set in the Gaussian94 format, see [Gaussian94].
+pub fn write(basis: &BasisSet) -> Result<String, AtoError> {
+    Gaussian94.write(basis)
+}
+
+/// The Gaussian94 format.
+///
+/// Consecutive s and p functions with the same exponents are written as one `SP` shell.
+/// Spin-orbit ECPs can not be represented in this format.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gaussian94;
+
+impl BasisFormat for Gaussian94 {
+    fn write_elements(&self, basis: &BasisSet, elements: &[Element]) -> Result<String, AtoError> {
+        let elements = gaussian_elements(basis, elements)?;
+        let mut txt = String::new();
+        for element in elements.iter() {
+            if let Some(functions) = basis.basis_functions.get(element) {
+                txt += &format!("{}     0\n", element.symbol());
+                txt += &write_shells(functions);
+                txt += "****\n";
+            }
+        }
+        for element in elements.iter() {
+            if let Some(ecp) = basis.ecps.get(element) {
+                txt += &write_ecp(*element, ecp)?;
+            }
+        }
+        Ok(txt)
+    }
+}
+
+/// Write the shells of one element, without the header and the terminating `****`.
+pub(crate) fn write_shells(functions: &[BasisFunction]) -> String {
+    let mut txt = String::new();
+    let mut i = 0;
+    while i < functions.len() {
+        let function = &functions[i];
+        let sp_partner = functions.get(i + 1).filter(|next| {
+            function.l == AngularMomentum::S
+                && next.l == AngularMomentum::P
+                && next.exponents == function.exponents
+        });
+        match sp_partner {
+            Some(p) => {
+                txt += &format!("SP   {}   1.00\n", function.exponents.len());
+                for ((e, cs), cp) in function
+                    .exponents
+                    .iter()
+                    .zip(function.coefficients.iter())
+                    .zip(p.coefficients.iter())
+                {
+                    txt += &format!(
+                        "{:>22} {:>22} {:>22}\n",
+                        number(*e),
+                        number(*cs),
+                        number(*cp)
+                    );
+                }
+                i += 2;
+            }
+            None => {
+                txt += &format!(
+                    "{:<5}{}   1.00\n",
+                    label(function.l).to_ascii_uppercase(),
+                    function.exponents.len()
+                );
+                for (e, c) in function.exponents.iter().zip(function.coefficients.iter()) {
+                    txt += &format!("{:>22} {:>22}\n", number(*e), number(*c));
+                }
+                i += 1;
+            }
+        }
+    }
+    txt
+}
+
+/// Write the ECP of one element. The local part comes first, followed by the semi-local
+/// projectors in the order of their angular momentum.
+pub(crate) fn write_ecp(
+    element: Element,
+    ecp: &EffectiveCorePotential,
+) -> Result<String, AtoError> {
+    let blocks = ecp_blocks(ecp)?;
+    let max_l = match blocks.first() {
+        Some((l, _)) => *l,
+        None => return Ok(String::new()),
+    };
+    let mut txt = format!("{}     0\n", element.symbol());
+    txt += &format!(
+        "{}-ECP     {}     {}\n",
+        element.symbol(),
+        max_l as u8,
+        ecp.core_electrons
+    );
+    for (l, projectors) in blocks {
+        let terms = ecp_terms(&projectors);
+        if l == max_l {
+            txt += &format!("{} potential\n  {}\n", label(l), terms.len());
+        } else {
+            txt += &format!(
+                "{}-{} potential\n  {}\n",
+                label(l),
+                label(max_l),
+                terms.len()
+            );
+        }
+        for (r, e, c) in terms {
+            txt += &format!("{}  {:>22} {:>22}\n", r, number(e), number(c));
+        }
+    }
+    Ok(txt)
+}
+
+/// Format a number in the Fortran notation of Gaussian, e.g. `1.543289673D-01`.
+///
+/// The shortest representation that parses back to the same value is used, so that writing
+/// and reading a basis set does not change it.
+fn number(x: f64) -> String {
+    let repr = format!("{:e}", x);
+    let (mantissa, exponent) = repr.split_once('e').unwrap_or((&repr, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let mantissa = if mantissa.contains('.') {
+        mantissa.to_owned()
+    } else {
+        format!("{}.0", mantissa)
+    };
+    format!(
+        "{}D{}{:02}",
+        mantissa,
+        if exponent < 0 { '-' } else { '+' },
+        exponent.abs()
+    )
+}
+
+fn parse_error(msg: &str) -> AtoError {
+    AtoError::Parse(format!("invalid Gaussian94 data: {}", msg))
+}
+
+fn parse_number(value: &str) -> Result<f64, AtoError> {
+    value
+        .replace(['D', 'd'], "E")
+        .parse::<f64>()
+        .map_err(|_| AtoError::Parse(format!("{} is not a valid number", value)))
+}
+
+/// Parse the header of an element block, e.g. `C     0` or `-C 0`.
+fn parse_element(line: &str) -> Result<Element, AtoError> {
+    let symbol = line
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches('-');
+    match Element::from(symbol) {
+        Element::Dummy => Err(parse_error(&format!("{} is not an element", symbol))),
+        element => Ok(element),
+    }
+}
+
+/// Check whether the line is the header of an ECP, e.g. `I-ECP 3 28` or `ECP28MWB 3 28`.
+///
+/// The name of the ECP is arbitrary, so the header is recognized by the highest angular
+/// momentum and the number of core electrons, which are integers. Unlike in the header of a
+/// shell, there is no scale factor.
+fn is_ecp_header(line: &str) -> bool {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    match tokens.as_slice() {
+        [name, max_l, core_electrons] => {
+            !name.eq_ignore_ascii_case("SP")
+                && from_label(name).is_none()
+                && max_l.parse::<u8>().is_ok()
+                && core_electrons.parse::<usize>().is_ok()
+        }
+        _ => false,
+    }
+}
+
+/// Parse the angular momentum of a shell, e.g. `D`.
+fn parse_angular_momentum(symbol: &str) -> Result<AngularMomentum, AtoError> {
+    from_label(symbol).ok_or_else(|| parse_error(&format!("{} is not an angular momentum", symbol)))
+}
+
+/// Parse the next `n` lines, that each contain `columns` numbers.
+fn read_rows<'a, I: Iterator<Item = &'a str>>(
+    lines: &mut I,
+    n: usize,
+    columns: usize,
+) -> Result<Vec<Vec<&'a str>>, AtoError> {
+    (0..n)
+        .map(|_| {
+            let line = lines
+                .next()
+                .ok_or_else(|| parse_error("the data ends within a block"))?;
+            let row: Vec<&str> = line.split_whitespace().collect();
+            if row.len() < columns {
+                return Err(parse_error(&format!(
+                    "expected {} values in the line {}",
+                    columns, line
+                )));
+            }
+            Ok(row)
+        })
+        .collect()
+}
+
+/// Parse a shell, whose header line was already read.
+fn read_shell<'a, I: Iterator<Item = &'a str>>(
+    header: &str,
+    lines: &mut I,
+) -> Result<Vec<BasisFunction>, AtoError> {
+    let tokens: Vec<&str> = header.split_whitespace().collect();
+    if tokens.len() < 2 {
+        return Err(parse_error(&format!("{} is not a shell header", header)));
+    }
+    let n: usize = tokens[1]
+        .parse()
+        .map_err(|_| parse_error(&format!("{} is not a shell header", header)))?;
+    let scale: f64 = match tokens.get(2) {
+        Some(scale) => parse_number(scale)?,
+        None => 1.0,
+    };
+    let momenta: Vec<AngularMomentum> = if tokens[0].eq_ignore_ascii_case("SP") {
+        vec![AngularMomentum::S, AngularMomentum::P]
+    } else {
+        vec![parse_angular_momentum(tokens[0])?]
+    };
+
+    let rows = read_rows(lines, n, momenta.len() + 1)?;
+    let exponents: Vec<f64> = rows
+        .iter()
+        .map(|row| Ok(parse_number(row[0])? * scale * scale))
+        .collect::<Result<_, AtoError>>()?;
+    momenta
+        .into_iter()
+        .enumerate()
+        .map(|(i, l)| {
+            Ok(BasisFunction::new(
+                l,
+                exponents.clone(),
+                rows.iter()
+                    .map(|row| parse_number(row[i + 1]))
+                    .collect::<Result<_, AtoError>>()?,
+                FunctionType::Spherical,
+            ))
+        })
+        .collect()
+}
+
+/// Parse an ECP block, whose element line was already read.
+fn read_ecp<'a, I: Iterator<Item = &'a str>>(
+    lines: &mut I,
+) -> Result<EffectiveCorePotential, AtoError> {
+    let header = lines.next().unwrap_or_default();
+    let tokens: Vec<&str> = header.split_whitespace().collect();
+    let invalid = || parse_error(&format!("{} is not an ECP header", header));
+    let max_l: u8 = tokens
+        .get(1)
+        .and_then(|l| l.parse().ok())
+        .ok_or_else(invalid)?;
+    let core_electrons: usize = tokens
+        .get(2)
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(invalid)?;
+
+    // The local part with the highest angular momentum comes first.
+    let mut projectors: Vec<EcpProjector> = Vec::new();
+    for l in std::iter::once(max_l).chain(0..max_l) {
+        // The name of the block, e.g. `s-f potential`, is only a comment.
+        lines
+            .next()
+            .ok_or_else(|| parse_error("the data ends within an ECP"))?;
+        let n: usize = lines
+            .next()
+            .and_then(|n| n.trim().parse().ok())
+            .ok_or_else(|| parse_error("an ECP block does not contain its number of terms"))?;
+        let rows = read_rows(lines, n, 3)?;
+        projectors.push(EcpProjector::new(
+            AngularMomentum::try_from(l)
+                .map_err(|_| parse_error(&format!("{} is not an angular momentum", l)))?,
+            EcpType::Scalar,
+            rows.iter()
+                .map(|row| {
+                    row[0]
+                        .parse()
+                        .map_err(|_| parse_error(&format!("{} is not an r-exponent", row[0])))
+                })
+                .collect::<Result<_, AtoError>>()?,
+            rows.iter()
+                .map(|row| parse_number(row[1]))
+                .collect::<Result<_, AtoError>>()?,
+            rows.iter()
+                .map(|row| parse_number(row[2]))
+                .collect::<Result<_, AtoError>>()?,
+        ));
+    }
+    // Keep the order of the BSE data, where the projectors are sorted by angular momentum.
+    projectors.rotate_left(1);
+    Ok(EffectiveCorePotential {
+        core_electrons,
+        projectors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::angular_momentum::AngularMomentum;
+    use crate::basis_set::BasisSet;
+    use crate::bse::basisset_json::InputData;
+    use crate::elements::Element;
+    use crate::error::AtoError;
+    use crate::formats::gaussian94::{number, read, write};
+    use crate::formats::{BasisFormat, Psi4};
+    use std::convert::TryFrom;
+
+    /// STO-3G for H and C, as exported by the BSE.
+    const STO3G_GBS: &str = "\
+!----------------------------------------------------------------------
+! Basis Set Exchange
+! Version v0.9
+! https://www.basissetexchange.org
+!----------------------------------------------------------------------
+!   Basis set: STO-3G
+! Description: STO-3G Minimal Basis (3 functions/AO)
+!        Role: orbital
+!     Version: 1  (Data from Gaussian09)
+!----------------------------------------------------------------------
+
+
+H     0
+S    3   1.00
+      0.3425250914D+01       0.1543289673D+00
+      0.6239137298D+00       0.5353281423D+00
+      0.1688554040D+00       0.4446345422D+00
+****
+C     0
+S    3   1.00
+      0.7161683735D+02       0.1543289673D+00
+      0.1304509632D+02       0.5353281423D+00
+      0.3530512160D+01       0.4446345422D+00
+SP   3   1.00
+      0.2941249355D+01      -0.9996722919D-01       0.1559162750D+00
+      0.6834830964D+00       0.3995128261D+00       0.6076837186D+00
+      0.2222899159D+00       0.7001154689D+00       0.3919573931D+00
+****
+";
+
+    /// The same basis set in the BSE JSON format.
+    const STO3G_JSON: &str = r#"
+    {
+        "revision_description": "Data from Gaussian09",
+        "revision_date": "2018-06-19",
+        "elements": {
+            "1": {
+                "electron_shells": [
+                    {
+                        "function_type": "gto",
+                        "region": "",
+                        "angular_momentum": [0],
+                        "exponents": ["0.3425250914E+01", "0.6239137298E+00", "0.1688554040E+00"],
+                        "coefficients": [["0.1543289673E+00", "0.5353281423E+00", "0.4446345422E+00"]]
+                    }
+                ],
+                "references": []
+            },
+            "6": {
+                "electron_shells": [
+                    {
+                        "function_type": "gto",
+                        "region": "",
+                        "angular_momentum": [0],
+                        "exponents": ["0.7161683735E+02", "0.1304509632E+02", "0.3530512160E+01"],
+                        "coefficients": [["0.1543289673E+00", "0.5353281423E+00", "0.4446345422E+00"]]
+                    },
+                    {
+                        "function_type": "gto",
+                        "region": "",
+                        "angular_momentum": [0, 1],
+                        "exponents": ["0.2941249355E+01", "0.6834830964E+00", "0.2222899159E+00"],
+                        "coefficients": [["-0.9996722919E-01", "0.3995128261E+00", "0.7001154689E+00"],
+                                         ["0.1559162750E+00", "0.6076837186E+00", "0.3919573931E+00"]]
+                    }
+                ],
+                "references": []
+            }
+        },
+        "version": "1",
+        "function_types": ["gto"],
+        "names": ["STO-3G"],
+        "tags": [],
+        "family": "sto",
+        "description": "STO-3G Minimal Basis (3 functions/AO)",
+        "role": "orbital",
+        "name": "STO-3G"
+    }"#;
+
+    /// The ECP of iodine of def2-SVP, as exported by the BSE.
+    const IODINE_ECP_GBS: &str = "\
+I     0
+I-ECP     3     28
+f potential
+  1
+2      1.0000000              0.0000000
+s-f potential
+  3
+2     40.0337391             49.9942930
+2     17.3537680            281.0264832
+2      8.8519661             61.5570696
+p-f potential
+  2
+2     15.7205298             67.4185137
+2     15.2037596            134.8067270
+d-f potential
+  1
+2     15.0691670             14.3904750
+";
+
+    fn assert_same(a: &BasisSet, b: &BasisSet) {
+        assert_eq!(a.basis_functions.len(), b.basis_functions.len());
+        for (element, functions) in a.basis_functions.iter() {
+            let other = &b.basis_functions[element];
+            assert_eq!(functions.len(), other.len());
+            for (f, g) in functions.iter().zip(other.iter()) {
+                assert_eq!(f.l, g.l);
+                assert_eq!(f.exponents, g.exponents);
+                assert_eq!(f.coefficients, g.coefficients);
+            }
+        }
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number(0.1543289673), "1.543289673D-01");
+        assert_eq!(number(-71.61683735), "-7.161683735D+01");
+        assert_eq!(number(1.0), "1.0D+00");
+        assert_eq!(number(2.5e-120), "2.5D-120");
+    }
+
+    #[test]
+    fn test_read_bse_export() {
+        let basis = read("STO-3G", STO3G_GBS).unwrap();
+        let data: InputData = serde_json::from_str(STO3G_JSON).unwrap();
+        assert_same(&basis, &BasisSet::try_from(data).unwrap());
+        let carbon = &basis.basis_functions[&Element::C];
+        assert_eq!(carbon[2].l, AngularMomentum::P);
+        assert_eq!(carbon[2].exponents, carbon[1].exponents);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let basis = read("STO-3G", STO3G_GBS).unwrap();
+        let txt = write(&basis).unwrap();
+        assert!(txt.starts_with("H     0\nS    3   1.00\n"));
+        assert!(txt.contains("SP   3   1.00\n"));
+        assert_same(&basis, &read("STO-3G", &txt).unwrap());
+        assert_eq!(txt, write(&read("STO-3G", &txt).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn test_scale_factor() {
+        let basis = read("test", "H 0\nS 1 2.00\n 0.5 1.0\n****\n").unwrap();
+        assert_eq!(basis.basis_functions[&Element::H][0].exponents, vec![2.0]);
+    }
+
+    #[test]
+    fn test_ecp() {
+        let basis = read("def2-SVP", IODINE_ECP_GBS).unwrap();
+        let ecp = basis.ecp(Element::I).unwrap();
+        assert_eq!(ecp.core_electrons, 28);
+        assert_eq!(ecp.max_l(), Some(AngularMomentum::F));
+        assert_eq!(ecp.projectors[0].l, AngularMomentum::S);
+        assert_eq!(ecp.projectors[0].exponents.len(), 3);
+        assert_eq!(ecp.local()[0].coefficients, vec![0.0]);
+
+        let txt = write(&basis).unwrap();
+        assert!(txt.contains("I-ECP     3     28\nf potential\n  1\n"));
+        assert!(txt.contains("s-f potential\n  3\n"));
+        let again = read("def2-SVP", &txt).unwrap();
+        let projectors = &again.ecp(Element::I).unwrap().projectors;
+        assert_eq!(projectors.len(), 4);
+        assert_eq!(projectors[1].coefficients, vec![67.4185137, 134.8067270]);
+    }
+
+    #[test]
+    fn test_leading_separator() {
+        let basis = read("STO-3G", &format!("****\n{}", STO3G_GBS)).unwrap();
+        assert_same(&basis, &read("STO-3G", STO3G_GBS).unwrap());
+        // The output of Psi4 starts with a separator after the type of the functions.
+        let txt = Psi4.write(&basis).unwrap();
+        let again = read("STO-3G", txt.trim_start_matches("spherical\n")).unwrap();
+        assert_same(&basis, &again);
+    }
+
+    #[test]
+    fn test_ecp_name() {
+        let stuttgart = IODINE_ECP_GBS.replace("I-ECP", "ECP28MWB");
+        let basis = read("def2-SVP", &stuttgart).unwrap();
+        let ecp = basis.ecp(Element::I).unwrap();
+        assert_eq!(ecp.core_electrons, 28);
+        assert_eq!(ecp.projectors.len(), 4);
+        // Shells with an integer scale factor are no ECP.
+        let basis = read("test", "H 0\nS 1 1\n 0.5 1.0\n****\n").unwrap();
+        assert_eq!(basis.basis_functions[&Element::H].len(), 1);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(
+            read("test", "H 0\nS 2 1.00\n 0.5 1.0\n****\n"),
+            Err(AtoError::Parse(_))
+        ));
+        assert!(matches!(
+            read("test", "Xx 0\n****\n"),
+            Err(AtoError::Parse(_))
+        ));
+        assert!(matches!(
+            read("test", "H 0\nS 1 1.00\n 0.5 1.0\n"),
+            Err(AtoError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,317 @@
+//! Readers and writers for the text formats of quantum chemistry programs.
+//!
+//! All writers implement [BasisFormat], so that the basis set of a molecule can be written
+//! for any program:
+//!
+//! ```
+//! use ato::elements::Element;
+//! use ato::formats::{gaussian94, BasisFormat, NWChem};
+//! let basis = gaussian94::read("mini", "H 0\nS 1 1.00\n 0.5 1.0\n****\nC 0\nS 1 1.00\n 2.0 1.0\n****\n").unwrap();
+//! let txt = NWChem.write_elements(&basis, &[Element::H, Element::H]).unwrap();
+//! assert!(txt.contains("H    S") && !txt.contains("C    S"));
+//! ```
+pub mod bse_json;
+pub mod cfour;
+pub mod gaussian94;
+pub mod molpro;
+pub mod nwchem;
+pub mod orca;
+pub mod psi4;
+pub mod turbomole;
+
+pub use bse_json::BseJson;
+pub use cfour::Cfour;
+pub use gaussian94::Gaussian94;
+pub use molpro::Molpro;
+pub use nwchem::NWChem;
+pub use orca::Orca;
+pub use psi4::Psi4;
+pub use turbomole::Turbomole;
+
+use crate::angular_momentum::AngularMomentum;
+use crate::basis_function::{BasisFunction, FunctionType};
+use crate::basis_set::BasisSet;
+use crate::ecp::{EcpProjector, EcpType, EffectiveCorePotential};
+use crate::elements::Element;
+use crate::error::AtoError;
+
+/// A text format in which basis sets can be written.
+pub trait BasisFormat {
+    /// Write the basis functions and ECPs of the given elements.
+    ///
+    /// Every element is written once, in the order of the atomic numbers, so the elements of
+    /// all atoms of a molecule can be passed directly. An error is returned if the basis set
+    /// does not contain one of the elements.
+    fn write_elements(&self, basis: &BasisSet, elements: &[Element]) -> Result<String, AtoError>;
+
+    /// Write all elements of the basis set.
+    fn write(&self, basis: &BasisSet) -> Result<String, AtoError> {
+        let elements: Vec<Element> = basis
+            .basis_functions
+            .keys()
+            .chain(basis.ecps.keys())
+            .chain(basis.slater_functions.keys())
+            .copied()
+            .collect();
+        self.write_elements(basis, &elements)
+    }
+}
+
+/// Sorted and deduplicated elements, which must all be part of the basis set.
+pub(crate) fn selected_elements(
+    basis: &BasisSet,
+    elements: &[Element],
+) -> Result<Vec<Element>, AtoError> {
+    let mut selected: Vec<Element> = elements.to_vec();
+    selected.sort();
+    selected.dedup();
+    for element in selected.iter() {
+        if !basis.basis_functions.contains_key(element)
+            && !basis.ecps.contains_key(element)
+            && !basis.slater_functions.contains_key(element)
+        {
+            return Err(AtoError::MissingElement {
+                basis: basis.name.clone(),
+                element: *element,
+            });
+        }
+    }
+    Ok(selected)
+}
+
+/// Like [selected_elements], for the program formats that only support Gaussian functions.
+///
+/// Slater-type shells have to be expanded first, see [BasisSet::with_gaussian_expansion].
+pub(crate) fn gaussian_elements(
+    basis: &BasisSet,
+    elements: &[Element],
+) -> Result<Vec<Element>, AtoError> {
+    let selected = selected_elements(basis, elements)?;
+    if selected
+        .iter()
+        .any(|element| basis.slater_functions.contains_key(element))
+    {
+        return Err(AtoError::UnsupportedFunctionType("sto".to_owned()));
+    }
+    Ok(selected)
+}
+
+/// Basis functions of an element, or nothing for elements that only have an ECP.
+pub(crate) fn functions<'a>(basis: &'a BasisSet, element: &Element) -> &'a [BasisFunction] {
+    basis
+        .basis_functions
+        .get(element)
+        .map_or(&[], |functions| functions.as_slice())
+}
+
+/// Letter of an angular momentum as it is used by most programs, where `j` is skipped.
+pub(crate) fn label(l: AngularMomentum) -> char {
+    const LABELS: [char; 11] = ['s', 'p', 'd', 'f', 'g', 'h', 'i', 'k', 'l', 'm', 'n'];
+    LABELS[l as usize]
+}
+
+/// Angular momentum of a letter, see [label].
+pub(crate) fn from_label(symbol: &str) -> Option<AngularMomentum> {
+    (0..=10u8)
+        .filter_map(|l| AngularMomentum::try_from(l).ok())
+        .find(|l| symbol.eq_ignore_ascii_case(&label(*l).to_string()))
+}
+
+/// Form of the functions for programs that only have a global setting.
+///
+/// The basis set is written as Cartesian if all shells with d or higher functions are
+/// Cartesian.
+pub(crate) fn global_function_type(basis: &BasisSet) -> FunctionType {
+    let mut higher = basis
+        .basis_functions
+        .values()
+        .flatten()
+        .filter(|function| function.l as u8 >= 2)
+        .peekable();
+    if higher.peek().is_some()
+        && higher.all(|function| function.function_type == FunctionType::Cartesian)
+    {
+        FunctionType::Cartesian
+    } else {
+        FunctionType::Spherical
+    }
+}
+
+/// Format a number without losing precision.
+pub(crate) fn number(x: f64) -> String {
+    if x == 0.0 || (1e-3..1e7).contains(&x.abs()) {
+        let repr = format!("{}", x);
+        if repr.contains('.') {
+            repr
+        } else {
+            format!("{}.0", repr)
+        }
+    } else {
+        format!("{:E}", x)
+    }
+}
+
+/// A contraction of primitives with one or more sets of coefficients.
+pub(crate) struct Contraction<'a> {
+    pub l: AngularMomentum,
+    pub exponents: &'a [f64],
+    pub coefficients: Vec<&'a [f64]>,
+}
+
+/// Group consecutive functions with the same angular momentum and exponents into general
+/// contractions, as they are given on the BSE.
+pub(crate) fn general_contractions(functions: &[BasisFunction]) -> Vec<Contraction<'_>> {
+    let mut contractions: Vec<Contraction> = Vec::new();
+    for function in functions.iter() {
+        match contractions.last_mut() {
+            Some(last) if last.l == function.l && last.exponents == function.exponents => {
+                last.coefficients.push(&function.coefficients)
+            }
+            _ => contractions.push(Contraction {
+                l: function.l,
+                exponents: &function.exponents,
+                coefficients: vec![&function.coefficients],
+            }),
+        }
+    }
+    contractions
+}
+
+/// The terms of an ECP, grouped by angular momentum. The local part comes first, followed by
+/// the semi-local projectors from s up to the local angular momentum.
+///
+/// Only scalar ECPs are supported by the program formats.
+pub(crate) fn ecp_blocks(
+    ecp: &EffectiveCorePotential,
+) -> Result<Vec<(AngularMomentum, Vec<&EcpProjector>)>, AtoError> {
+    if ecp.projectors.iter().any(|p| p.kind != EcpType::Scalar) {
+        return Err(AtoError::UnsupportedFunctionType(
+            EcpType::SpinOrbit.bse_name().to_owned(),
+        ));
+    }
+    let max_l = match ecp.max_l() {
+        Some(l) => l,
+        None => return Ok(Vec::new()),
+    };
+    let mut blocks = vec![(max_l, ecp.local())];
+    for l in 0..max_l as u8 {
+        let l = AngularMomentum::try_from(l).unwrap();
+        let projectors: Vec<&EcpProjector> =
+            ecp.semi_local().into_iter().filter(|p| p.l == l).collect();
+        blocks.push((l, projectors));
+    }
+    Ok(blocks)
+}
+
+/// All terms `(r_exponent, exponent, coefficient)` of the projectors.
+pub(crate) fn ecp_terms(projectors: &[&EcpProjector]) -> Vec<(i32, f64, f64)> {
+    projectors
+        .iter()
+        .flat_map(|p| {
+            p.r_exponents
+                .iter()
+                .zip(p.exponents.iter())
+                .zip(p.coefficients.iter())
+                .map(|((r, e), c)| (*r, *e, *c))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::angular_momentum::AngularMomentum;
+    use crate::basis_set::BasisSet;
+    use crate::elements::Element;
+    use crate::error::AtoError;
+    use crate::formats::{
+        from_label, gaussian94, general_contractions, label, number, BasisFormat, Cfour,
+        Gaussian94, Molpro, NWChem, Orca, Psi4, Turbomole,
+    };
+
+    /// Hydrogen with a general contraction, carbon with an SP shell and a d shell, and iodine
+    /// with only an ECP.
+    pub(crate) fn basis() -> BasisSet {
+        gaussian94::read(
+            "test",
+            "\
+H     0
+S    2   1.00
+  13.01  0.0196850
+  1.962  0.1379770
+S    2   1.00
+  13.01  0.0
+  1.962  1.0
+P    1   1.00
+  0.727  1.0
+****
+C     0
+S    1   1.00
+  71.6168370  0.15432897
+SP   1   1.00
+  2.9412494  -0.09996723  0.15591627
+D    1   1.00
+  0.8  1.0
+****
+I     0
+I-ECP     2     28
+d potential
+  1
+2      1.0              0.0
+s-d potential
+  2
+2     40.0337391             49.9942930
+2     17.3537680            281.0264832
+p-d potential
+  1
+2     15.7205298             67.4185137
+",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_labels() {
+        assert_eq!(label(AngularMomentum::I), 'i');
+        assert_eq!(label(AngularMomentum::J), 'k');
+        assert_eq!(from_label("K"), Some(AngularMomentum::J));
+        assert_eq!(from_label("j"), None);
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number(1.0), "1.0");
+        assert_eq!(number(-0.09996723), "-0.09996723");
+        assert_eq!(number(1.5e-5), "1.5E-5");
+        assert_eq!(number(0.0), "0.0");
+    }
+
+    #[test]
+    fn test_general_contractions() {
+        let basis = basis();
+        let contractions = general_contractions(&basis.basis_functions[&Element::H]);
+        assert_eq!(contractions.len(), 2);
+        assert_eq!(contractions[0].coefficients.len(), 2);
+        assert_eq!(contractions[1].l, AngularMomentum::P);
+    }
+
+    #[test]
+    fn test_missing_element() {
+        let basis = basis();
+        let formats: Vec<Box<dyn BasisFormat>> = vec![
+            Box::new(Gaussian94),
+            Box::new(NWChem),
+            Box::new(Orca),
+            Box::new(Psi4),
+            Box::new(Molpro),
+            Box::new(Turbomole),
+            Box::new(Cfour),
+        ];
+        for format in formats.iter() {
+            assert!(format.write(&basis).is_ok());
+            assert!(matches!(
+                format.write_elements(&basis, &[Element::H, Element::O]),
+                Err(AtoError::MissingElement { .. })
+            ));
+        }
+    }
+}
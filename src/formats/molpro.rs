@@ -0,0 +1,81 @@
+use crate::basis_set::BasisSet;
+use crate::elements::Element;
+use crate::error::AtoError;
+use crate::formats::{
+    ecp_blocks, ecp_terms, functions, gaussian_elements, general_contractions, label, number,
+    BasisFormat,
+};
+
+/// The `basis={...}` block of a Molpro input.
+///
+/// The exponents of a general contraction are written once, followed by one `c` line for
+/// every set of coefficients.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Molpro;
+
+impl BasisFormat for Molpro {
+    fn write_elements(&self, basis: &BasisSet, elements: &[Element]) -> Result<String, AtoError> {
+        let elements = gaussian_elements(basis, elements)?;
+        let mut txt = "basis={\n".to_owned();
+        for element in elements.iter() {
+            let contractions = general_contractions(functions(basis, element));
+            if !contractions.is_empty() {
+                txt += &format!("! {}\n", element.name());
+            }
+            for contraction in contractions {
+                txt += &format!("{}, {}", label(contraction.l), element.symbol());
+                for e in contraction.exponents {
+                    txt += &format!(", {}", number(*e));
+                }
+                txt += "\n";
+                for coefficients in contraction.coefficients {
+                    txt += &format!("c, 1.{}", coefficients.len());
+                    for c in coefficients {
+                        txt += &format!(", {}", number(*c));
+                    }
+                    txt += "\n";
+                }
+            }
+        }
+        for element in elements.iter() {
+            if let Some(ecp) = basis.ecps.get(element) {
+                let blocks = ecp_blocks(ecp)?;
+                let max_l = blocks.first().map_or(0, |(l, _)| *l as u8);
+                txt += &format!(
+                    "ECP, {}, {}, {};\n",
+                    element.symbol(),
+                    ecp.core_electrons,
+                    max_l
+                );
+                for (i, (l, projectors)) in blocks.into_iter().enumerate() {
+                    let terms = ecp_terms(&projectors);
+                    if i == 0 {
+                        txt += &format!("{}; ! ul potential\n", terms.len());
+                    } else {
+                        txt += &format!("{}; ! {}-ul potential\n", terms.len(), label(l));
+                    }
+                    for (r, e, c) in terms {
+                        txt += &format!("{},{},{};\n", r, number(e), number(c));
+                    }
+                }
+            }
+        }
+        txt += "}\n";
+        Ok(txt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formats::tests::basis;
+    use crate::formats::{BasisFormat, Molpro};
+
+    #[test]
+    fn test_molpro() {
+        let txt = Molpro.write(&basis()).unwrap();
+        assert!(txt.starts_with("basis={\n! Hydrogen\ns, H, 13.01, 1.962\n"));
+        assert!(txt.contains("c, 1.2, 0.019685, 0.137977\nc, 1.2, 0.0, 1.0\n"));
+        assert!(txt.contains("ECP, I, 28, 2;\n1; ! ul potential\n2,1.0,0.0;\n2; ! s-ul"));
+        assert!(txt.ends_with("}\n"));
+    }
+}
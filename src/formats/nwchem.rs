@@ -0,0 +1,91 @@
+use crate::basis_function::FunctionType;
+use crate::basis_set::BasisSet;
+use crate::elements::Element;
+use crate::error::AtoError;
+use crate::formats::{
+    ecp_blocks, ecp_terms, functions, gaussian_elements, general_contractions,
+    global_function_type, label, number, BasisFormat,
+};
+
+/// The `BASIS` and `ECP` blocks of an NWChem input.
+///
+/// General contractions are written as one shell with several coefficient columns.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NWChem;
+
+impl BasisFormat for NWChem {
+    fn write_elements(&self, basis: &BasisSet, elements: &[Element]) -> Result<String, AtoError> {
+        let elements = gaussian_elements(basis, elements)?;
+        let kind = match global_function_type(basis) {
+            FunctionType::Spherical => "SPHERICAL",
+            FunctionType::Cartesian => "CARTESIAN",
+        };
+        let mut txt = format!("BASIS \"ao basis\" {} PRINT\n", kind);
+        for element in elements.iter() {
+            for contraction in general_contractions(functions(basis, element)) {
+                txt += &format!(
+                    "{}    {}\n",
+                    element.symbol(),
+                    label(contraction.l).to_ascii_uppercase()
+                );
+                for (i, e) in contraction.exponents.iter().enumerate() {
+                    txt += &format!("{:>20}", number(*e));
+                    for coefficients in contraction.coefficients.iter() {
+                        txt += &format!(" {:>20}", number(coefficients[i]));
+                    }
+                    txt += "\n";
+                }
+            }
+        }
+        txt += "END\n";
+
+        let ecps: Vec<&Element> = elements
+            .iter()
+            .filter(|element| basis.ecps.contains_key(element))
+            .collect();
+        if ecps.is_empty() {
+            return Ok(txt);
+        }
+        txt += "ECP\n";
+        for element in ecps {
+            let ecp = &basis.ecps[element];
+            txt += &format!("{} nelec {}\n", element.symbol(), ecp.core_electrons);
+            for (i, (l, projectors)) in ecp_blocks(ecp)?.into_iter().enumerate() {
+                // The local part is called `ul` in NWChem.
+                let name = if i == 0 {
+                    "ul".to_owned()
+                } else {
+                    label(l).to_ascii_uppercase().to_string()
+                };
+                txt += &format!("{} {}\n", element.symbol(), name);
+                for (r, e, c) in ecp_terms(&projectors) {
+                    txt += &format!("{}  {:>20} {:>20}\n", r, number(e), number(c));
+                }
+            }
+        }
+        txt += "END\n";
+        Ok(txt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::elements::Element;
+    use crate::formats::tests::basis;
+    use crate::formats::{BasisFormat, NWChem};
+
+    #[test]
+    fn test_nwchem() {
+        let basis = basis();
+        let txt = NWChem.write(&basis).unwrap();
+        assert!(txt.starts_with("BASIS \"ao basis\" SPHERICAL PRINT\nH    S\n"));
+        assert!(txt.contains("               13.01             0.019685                  0.0\n"));
+        assert!(txt.contains("C    S\n"));
+        assert!(txt.contains("ECP\nI nelec 28\nI ul\n"));
+        assert!(txt.contains("I S\n2            40.0337391            49.994293\n"));
+
+        let txt = NWChem.write_elements(&basis, &[Element::H]).unwrap();
+        assert!(!txt.contains("C    S"));
+        assert!(!txt.contains("ECP"));
+    }
+}
@@ -0,0 +1,84 @@
+use crate::basis_set::BasisSet;
+use crate::elements::Element;
+use crate::error::AtoError;
+use crate::formats::{
+    ecp_blocks, ecp_terms, functions, gaussian_elements, label, number, BasisFormat,
+};
+
+/// The `%basis` block of an ORCA input with `NewGTO` and `NewECP` entries.
+///
+/// ORCA has no general contractions, so every contraction is written as its own shell.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Orca;
+
+impl BasisFormat for Orca {
+    fn write_elements(&self, basis: &BasisSet, elements: &[Element]) -> Result<String, AtoError> {
+        let elements = gaussian_elements(basis, elements)?;
+        let mut txt = "%basis\n".to_owned();
+        for element in elements.iter() {
+            let functions = functions(basis, element);
+            if !functions.is_empty() {
+                txt += &format!("  NewGTO {}\n", element.symbol());
+                for function in functions {
+                    txt += &format!(
+                        "    {} {}\n",
+                        label(function.l).to_ascii_uppercase(),
+                        function.exponents.len()
+                    );
+                    for (i, (e, c)) in function
+                        .exponents
+                        .iter()
+                        .zip(function.coefficients.iter())
+                        .enumerate()
+                    {
+                        txt += &format!("    {:>3} {:>20} {:>20}\n", i + 1, number(*e), number(*c));
+                    }
+                }
+                txt += "  end\n";
+            }
+            if let Some(ecp) = basis.ecps.get(element) {
+                let blocks = ecp_blocks(ecp)?;
+                txt += &format!("  NewECP {}\n", element.symbol());
+                txt += &format!("    N_core {}\n", ecp.core_electrons);
+                if let Some((max_l, _)) = blocks.first() {
+                    txt += &format!("    lmax {}\n", label(*max_l));
+                }
+                // ORCA expects the projectors in the order of their angular momentum.
+                let mut blocks = blocks;
+                blocks.rotate_left(1);
+                for (l, projectors) in blocks {
+                    let terms = ecp_terms(&projectors);
+                    txt += &format!("    {} {}\n", label(l), terms.len());
+                    for (i, (r, e, c)) in terms.into_iter().enumerate() {
+                        txt += &format!(
+                            "    {:>3} {:>20} {:>20} {}\n",
+                            i + 1,
+                            number(e),
+                            number(c),
+                            r
+                        );
+                    }
+                }
+                txt += "  end\n";
+            }
+        }
+        txt += "end\n";
+        Ok(txt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formats::tests::basis;
+    use crate::formats::{BasisFormat, Orca};
+
+    #[test]
+    fn test_orca() {
+        let txt = Orca.write(&basis()).unwrap();
+        assert!(txt.starts_with("%basis\n  NewGTO H\n    S 2\n"));
+        // The general contraction of hydrogen is split into two shells.
+        assert_eq!(txt.matches("    S 2\n").count(), 2);
+        assert!(txt.contains("  NewECP I\n    N_core 28\n    lmax d\n    s 2\n"));
+        assert!(txt.ends_with("  end\nend\n"));
+    }
+}
@@ -0,0 +1,56 @@
+use crate::basis_function::FunctionType;
+use crate::basis_set::BasisSet;
+use crate::elements::Element;
+use crate::error::AtoError;
+use crate::formats::gaussian94::{write_ecp, write_shells};
+use crate::formats::{gaussian_elements, global_function_type, BasisFormat};
+
+/// The Psi4 flavour of the Gaussian94 format, as it is used in `.gbs` files and `basis`
+/// blocks of Psi4.
+///
+/// The first line declares whether the basis set is spherical or Cartesian.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Psi4;
+
+impl BasisFormat for Psi4 {
+    fn write_elements(&self, basis: &BasisSet, elements: &[Element]) -> Result<String, AtoError> {
+        let elements = gaussian_elements(basis, elements)?;
+        let mut txt = match global_function_type(basis) {
+            FunctionType::Spherical => "spherical\n\n****\n".to_owned(),
+            FunctionType::Cartesian => "cartesian\n\n****\n".to_owned(),
+        };
+        for element in elements.iter() {
+            if let Some(functions) = basis.basis_functions.get(element) {
+                txt += &format!("{}     0\n", element.symbol());
+                txt += &write_shells(functions);
+                txt += "****\n";
+            }
+        }
+        for element in elements.iter() {
+            if let Some(ecp) = basis.ecps.get(element) {
+                txt += &write_ecp(*element, ecp)?;
+            }
+        }
+        Ok(txt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::basis_function::FunctionType;
+    use crate::formats::tests::basis;
+    use crate::formats::{gaussian94, BasisFormat, Psi4};
+
+    #[test]
+    fn test_psi4() {
+        let basis = basis();
+        let txt = Psi4.write(&basis).unwrap();
+        assert!(txt.starts_with("spherical\n\n****\nH     0\n"));
+        assert!(txt.contains("I-ECP     2     28\n"));
+        // Apart from the header the data is the same as in the Gaussian94 format.
+        assert!(txt.ends_with(&gaussian94::write(&basis).unwrap()));
+
+        let basis = basis.with_function_type(FunctionType::Cartesian);
+        assert!(Psi4.write(&basis).unwrap().starts_with("cartesian\n"));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::basis_set::BasisSet;
+use crate::elements::Element;
+use crate::error::AtoError;
+use crate::formats::{
+    ecp_blocks, ecp_terms, functions, gaussian_elements, label, number, BasisFormat,
+};
+
+/// The `$basis` and `$ecp` data groups of Turbomole.
+///
+/// Turbomole has no general contractions, so every contraction is written as its own shell.
+/// The powers of r in the ECP are written in the Turbomole convention, which is the Gaussian
+/// one minus two.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Turbomole;
+
+impl BasisFormat for Turbomole {
+    fn write_elements(&self, basis: &BasisSet, elements: &[Element]) -> Result<String, AtoError> {
+        let elements = gaussian_elements(basis, elements)?;
+        let name = basis.name.to_lowercase();
+        let mut txt = "$basis\n*\n".to_owned();
+        for element in elements.iter() {
+            let functions = functions(basis, element);
+            if functions.is_empty() {
+                continue;
+            }
+            txt += &format!("{} {}\n*\n", element.symbol().to_lowercase(), name);
+            for function in functions {
+                txt += &format!("   {}  {}\n", function.exponents.len(), label(function.l));
+                for (e, c) in function.exponents.iter().zip(function.coefficients.iter()) {
+                    txt += &format!("  {:>20} {:>20}\n", number(*e), number(*c));
+                }
+            }
+            txt += "*\n";
+        }
+
+        let ecps: Vec<&Element> = elements
+            .iter()
+            .filter(|element| basis.ecps.contains_key(element))
+            .collect();
+        if !ecps.is_empty() {
+            txt += "$ecp\n*\n";
+            for element in ecps {
+                let ecp = &basis.ecps[element];
+                let blocks = ecp_blocks(ecp)?;
+                let max_l = match blocks.first() {
+                    Some((l, _)) => *l,
+                    None => continue,
+                };
+                txt += &format!("{} {}-ecp\n*\n", element.symbol().to_lowercase(), name);
+                txt += &format!(
+                    "  ncore = {}   lmax = {}\n#  coefficient   r^n   exponent\n",
+                    ecp.core_electrons, max_l as u8
+                );
+                for (i, (l, projectors)) in blocks.into_iter().enumerate() {
+                    if i == 0 {
+                        txt += &format!("{}\n", label(l));
+                    } else {
+                        txt += &format!("{}-{}\n", label(l), label(max_l));
+                    }
+                    for (r, e, c) in ecp_terms(&projectors) {
+                        txt += &format!("  {:>20} {:>3} {:>20}\n", number(c), r - 2, number(e));
+                    }
+                }
+                txt += "*\n";
+            }
+        }
+        txt += "$end\n";
+        Ok(txt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formats::tests::basis;
+    use crate::formats::{BasisFormat, Turbomole};
+
+    #[test]
+    fn test_turbomole() {
+        let txt = Turbomole.write(&basis()).unwrap();
+        assert!(txt.starts_with("$basis\n*\nh test\n*\n   2  s\n"));
+        assert!(txt.contains("*\n$ecp\n*\ni test-ecp\n*\n  ncore = 28   lmax = 2\n"));
+        assert!(txt.contains("s-d\n             49.994293   0           40.0337391\n"));
+        assert!(txt.ends_with("*\n$end\n"));
+    }
+}
@@ -1,8 +1,17 @@
 pub mod angular_momentum;
-mod basis_function;
+pub mod archive;
+pub mod autoaux;
+pub mod basis_function;
 pub mod basis_set;
-mod bse;
+pub mod bse;
+pub mod ecp;
+pub mod catalogue;
 pub mod elements;
+pub mod error;
 pub mod files;
+pub mod formats;
+pub mod manifest;
+pub mod references;
+pub mod slater_function;
 pub mod atomic_orbital;
 pub mod electron_shell;
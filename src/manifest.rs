@@ -0,0 +1,195 @@
+use crate::bse::http::InputVersion;
+use crate::files::{data_root, data_roots, write_atomic, BASIS_SUBDIRECTORY};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const MANIFEST: &str = "manifest.json";
+
+/// Record of the basis set files that were downloaded completely.
+///
+/// A file in the basis set directory is only considered to be valid, if it is listed in the
+/// manifest with the same size. Files of interrupted downloads are therefore detected and
+/// downloaded again. Every data root has its own manifest, see [crate::files::data_roots].
+///
+/// Data directories of older versions of this crate have no manifest. Their basis set files
+/// are added to a new manifest once, if they can be parsed, see [Manifest::read_from].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    pub basis_sets: BTreeMap<String, ManifestEntry>,
+    /// Data root of the manifest, the writable one if it is not set.
+    #[serde(skip)]
+    root: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestEntry {
+    /// Size of the basis set file in bytes.
+    pub size: u64,
+    /// Version of the basis set on the BSE.
+    #[serde(default)]
+    pub version: String,
+}
+
+impl ManifestEntry {
+    /// True if the version is newer than the one of the other entry. The versions of the BSE
+    /// are numbers, other versions are compared as text.
+    pub fn is_newer_than(&self, other: &ManifestEntry) -> bool {
+        match (self.version.parse::<u64>(), other.version.parse::<u64>()) {
+            (Ok(version), Ok(other)) => version > other,
+            _ => self.version > other.version,
+        }
+    }
+}
+
+impl Manifest {
+    /// Read the manifest of the writable data root. An empty manifest is returned if it does
+    /// not exist.
+    pub fn read() -> Result<Self> {
+        Self::read_from(&data_root()?)
+    }
+
+    /// Read the manifest of a data root.
+    ///
+    /// If it does not exist, the valid basis set files of the root are listed in a new
+    /// manifest, which is empty for a new data root.
+    pub fn read_from(root: &Path) -> Result<Self> {
+        let path = root.join(MANIFEST);
+        if !path.exists() {
+            return Self::migrate(root);
+        }
+        let data = fs::read_to_string(&path).context("Unable to read the manifest")?;
+        let mut manifest: Self =
+            serde_json::from_str(&data).context("Could not deserialize the manifest")?;
+        manifest.root = Some(root.to_owned());
+        Ok(manifest)
+    }
+
+    /// Manifest of the basis set files of a data root without manifest, which was written by
+    /// an older version of this crate. Files that can not be parsed, e.g. of an interrupted
+    /// download, are left out and downloaded again.
+    fn migrate(root: &Path) -> Result<Self> {
+        let mut manifest = Self {
+            root: Some(root.to_owned()),
+            ..Self::default()
+        };
+        let entries = match fs::read_dir(root.join(BASIS_SUBDIRECTORY)) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(manifest),
+        };
+        for entry in entries {
+            let path = entry.context("Could not list the basis set files")?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let id = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(id) if !id.starts_with('.') => id,
+                _ => continue,
+            };
+            let data = fs::read(&path).context("Could not read a basis set file")?;
+            if let Ok(basis) = serde_json::from_slice::<InputVersion>(&data) {
+                manifest.insert(id, data.len() as u64, &basis.version);
+            }
+        }
+        if !manifest.basis_sets.is_empty() {
+            // A manifest that was written in the meantime, e.g. by a download, is kept. The
+            // manifest of a read-only root is only migrated in memory.
+            let _ = manifest.write_new();
+        }
+        Ok(manifest)
+    }
+
+    /// Read the manifests of all data roots in their order.
+    pub fn read_all() -> Result<Vec<Self>> {
+        data_roots()?
+            .iter()
+            .map(|root| Self::read_from(root))
+            .collect()
+    }
+
+    /// Data root of the manifest.
+    pub fn root(&self) -> Result<PathBuf> {
+        match &self.root {
+            Some(root) => Ok(root.clone()),
+            None => data_root(),
+        }
+    }
+
+    pub fn write(&self) -> Result<()> {
+        write_atomic(
+            self.root()?.join(MANIFEST),
+            &serde_json::to_vec_pretty(self)?,
+        )
+    }
+
+    /// Write the manifest, unless one exists already.
+    fn write_new(&self) -> Result<()> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = self.root()?.join(MANIFEST);
+        let tmp_path = path.with_file_name(format!(
+            ".{}.{}.{}.tmp",
+            MANIFEST,
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)
+            .context("Could not write temporary file")?;
+        // Unlike a rename, a hard link does not replace an existing manifest.
+        let result = fs::hard_link(&tmp_path, &path).context("Could not move temporary file");
+        let _ = fs::remove_file(&tmp_path);
+        result
+    }
+
+    pub fn insert(&mut self, id: &str, size: u64, version: &str) {
+        self.basis_sets.insert(
+            id.to_owned(),
+            ManifestEntry {
+                size,
+                version: version.to_owned(),
+            },
+        );
+    }
+
+    /// Check that the basis set is listed in the manifest and its file is complete.
+    pub fn is_complete(&self, id: &str) -> Result<bool> {
+        let entry = match self.basis_sets.get(id) {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+        let path = self
+            .root()?
+            .join(format!("{}{}.json", BASIS_SUBDIRECTORY, id));
+        Ok(match fs::metadata(path) {
+            Ok(metadata) => metadata.len() == entry.size,
+            Err(_) => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::manifest::Manifest;
+
+    #[test]
+    fn test_missing_entry() {
+        let mut manifest = Manifest::default();
+        manifest.insert("sto-3g", 12, "1");
+        assert_eq!(manifest.basis_sets.get("sto-3g").unwrap().version, "1");
+        assert!(!manifest.is_complete("6-31g").unwrap());
+    }
+
+    #[test]
+    fn test_newer_version() {
+        let mut manifest = Manifest::default();
+        manifest.insert("old", 12, "9");
+        manifest.insert("new", 12, "10");
+        let entries = &manifest.basis_sets;
+        assert!(entries["new"].is_newer_than(&entries["old"]));
+        assert!(!entries["old"].is_newer_than(&entries["new"]));
+        assert!(!entries["new"].is_newer_than(&entries["new"]));
+    }
+}
@@ -0,0 +1,273 @@
+//! Bibliographic records of the basis sets and their citations as BibTeX or RIS.
+//!
+//! The records are requested from the references endpoint of the BSE and cached in the data
+//! directory next to the basis sets, see [crate::basis_set::BasisSet::citations].
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Format of a list of citations.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CitationFormat {
+    BibTex,
+    Ris,
+}
+
+/// A bibliographic record of the BSE, e.g. the paper in which a basis set was published.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Reference {
+    /// Key of the record on the BSE, e.g. `hehre1969a`.
+    pub key: String,
+    /// BibTeX entry type, e.g. `article` or `book`.
+    pub entry_type: String,
+    /// Authors in the form `Last, First`.
+    pub authors: Vec<String>,
+    pub editors: Vec<String>,
+    /// All further fields in their BibTeX names, e.g. `title`, `journal` or `doi`.
+    pub fields: BTreeMap<String, String>,
+}
+
+impl Reference {
+    /// Convert a record of the BSE, given as JSON object.
+    ///
+    /// The BSE names the entry type `_entry_type`, older data `type`, and sometimes uses
+    /// `page` for the pages.
+    pub(crate) fn from_bse(key: &str, record: &serde_json::Map<String, Value>) -> Self {
+        let names = |field: &str| -> Vec<String> {
+            match record.get(field) {
+                Some(Value::Array(values)) => values
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_owned))
+                    .collect(),
+                Some(Value::String(value)) => vec![value.clone()],
+                _ => Vec::new(),
+            }
+        };
+        let mut fields: BTreeMap<String, String> = BTreeMap::new();
+        let mut entry_type = "misc".to_owned();
+        for (name, value) in record.iter() {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                Value::Number(value) => value.to_string(),
+                _ => continue,
+            };
+            match name.as_str() {
+                "_entry_type" | "type" => entry_type = value,
+                "authors" | "editors" => {}
+                "page" => {
+                    fields.insert("pages".to_owned(), value);
+                }
+                _ => {
+                    fields.insert(name.clone(), value);
+                }
+            }
+        }
+        Self {
+            key: key.to_owned(),
+            entry_type,
+            authors: names("authors"),
+            editors: names("editors"),
+            fields,
+        }
+    }
+
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .get(name)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+}
+
+/// The paper of the Basis Set Exchange, which should be cited when the data is used.
+pub fn bse_reference() -> Reference {
+    let fields = [
+        (
+            "title",
+            "New Basis Set Exchange: An Open, Up-to-Date Resource for the Molecular Sciences Community",
+        ),
+        ("journal", "J. Chem. Inf. Model."),
+        ("volume", "59"),
+        ("number", "11"),
+        ("pages", "4814-4820"),
+        ("year", "2019"),
+        ("doi", "10.1021/acs.jcim.9b00725"),
+    ];
+    Reference {
+        key: "pritchard2019a".to_owned(),
+        entry_type: "article".to_owned(),
+        authors: [
+            "Pritchard, Benjamin P.",
+            "Altarawy, Doaa",
+            "Didier, Brett",
+            "Gibson, Tara D.",
+            "Windus, Theresa L.",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect(),
+        editors: Vec::new(),
+        fields: fields
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+    }
+}
+
+/// Write the references in the given format, in the given order.
+pub fn write(references: &[Reference], format: CitationFormat) -> String {
+    let entries: Vec<String> = references
+        .iter()
+        .map(|reference| match format {
+            CitationFormat::BibTex => bibtex(reference),
+            CitationFormat::Ris => ris(reference),
+        })
+        .collect();
+    entries.join("\n")
+}
+
+/// Fields that are written first, all others follow in alphabetical order.
+const FIELD_ORDER: [&str; 8] = [
+    "title",
+    "booktitle",
+    "journal",
+    "volume",
+    "number",
+    "pages",
+    "year",
+    "publisher",
+];
+
+fn ordered_fields(reference: &Reference) -> Vec<(&str, &str)> {
+    let mut fields: Vec<(&str, &str)> = FIELD_ORDER
+        .iter()
+        .filter_map(|name| reference.field(name).map(|value| (*name, value)))
+        .collect();
+    fields.extend(
+        reference
+            .fields
+            .iter()
+            .filter(|(name, value)| !FIELD_ORDER.contains(&name.as_str()) && !value.is_empty())
+            .map(|(name, value)| (name.as_str(), value.as_str())),
+    );
+    fields
+}
+
+fn bibtex(reference: &Reference) -> String {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    if !reference.authors.is_empty() {
+        fields.push(("author", reference.authors.join(" and ")));
+    }
+    if !reference.editors.is_empty() {
+        fields.push(("editor", reference.editors.join(" and ")));
+    }
+    fields.extend(
+        ordered_fields(reference)
+            .into_iter()
+            .map(|(name, value)| (name, value.to_owned())),
+    );
+    let mut txt = format!("@{}{{{},\n", reference.entry_type, reference.key);
+    for (name, value) in fields {
+        txt += &format!("    {} = {{{}}},\n", name, value);
+    }
+    txt += "}\n";
+    txt
+}
+
+fn ris(reference: &Reference) -> String {
+    let kind = match reference.entry_type.to_lowercase().as_str() {
+        "article" => "JOUR",
+        "book" => "BOOK",
+        "inbook" | "incollection" => "CHAP",
+        "inproceedings" | "conference" => "CPAPER",
+        "phdthesis" | "mastersthesis" => "THES",
+        "techreport" => "RPRT",
+        "unpublished" => "UNPB",
+        _ => "GEN",
+    };
+    let mut lines: Vec<(&str, String)> =
+        vec![("TY", kind.to_owned()), ("ID", reference.key.clone())];
+    lines.extend(reference.authors.iter().map(|name| ("AU", name.clone())));
+    lines.extend(reference.editors.iter().map(|name| ("ED", name.clone())));
+    for (name, value) in ordered_fields(reference) {
+        let tag = match name {
+            "title" => "TI",
+            "journal" | "booktitle" => "T2",
+            "volume" => "VL",
+            "number" => "IS",
+            "year" => "PY",
+            "publisher" => "PB",
+            "doi" => "DO",
+            "url" => "UR",
+            "isbn" | "issn" => "SN",
+            "note" => "N1",
+            "pages" => {
+                let mut pages = value.splitn(2, '-');
+                if let Some(start) = pages.next() {
+                    lines.push(("SP", start.trim().to_owned()));
+                }
+                if let Some(end) = pages.next() {
+                    lines.push(("EP", end.trim_start_matches('-').trim().to_owned()));
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        lines.push((tag, value.to_owned()));
+    }
+    let mut txt: String = lines
+        .into_iter()
+        .map(|(tag, value)| format!("{}  - {}\n", tag, value))
+        .collect();
+    txt += "ER  - \n";
+    txt
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::references::{bse_reference, write, CitationFormat, Reference};
+
+    fn hehre() -> Reference {
+        let record = serde_json::json!({
+            "_entry_type": "article",
+            "authors": ["Hehre, W. J.", "Stewart, R. F.", "Pople, J. A."],
+            "title": "Self-Consistent Molecular-Orbital Methods. I. Use of Gaussian Expansions of Slater-Type Atomic Orbitals",
+            "journal": "J. Chem. Phys.",
+            "volume": "51",
+            "page": "2657-2664",
+            "year": "1969",
+            "doi": "10.1063/1.1672392"
+        });
+        Reference::from_bse("hehre1969a", record.as_object().unwrap())
+    }
+
+    #[test]
+    fn test_from_bse() {
+        let reference = hehre();
+        assert_eq!(reference.entry_type, "article");
+        assert_eq!(reference.authors.len(), 3);
+        assert_eq!(reference.fields["pages"], "2657-2664");
+        assert!(!reference.fields.contains_key("authors"));
+    }
+
+    #[test]
+    fn test_bibtex() {
+        let txt = write(&[hehre()], CitationFormat::BibTex);
+        assert!(txt.starts_with(
+            "@article{hehre1969a,\n    author = {Hehre, W. J. and Stewart, R. F. and Pople, J. A.},\n    title = {Self-Consistent"
+        ));
+        assert!(txt.contains(
+            "    volume = {51},\n    pages = {2657-2664},\n    year = {1969},\n    doi = {10.1063/1.1672392},\n}\n"
+        ));
+    }
+
+    #[test]
+    fn test_ris() {
+        let txt = write(&[bse_reference(), hehre()], CitationFormat::Ris);
+        assert!(txt.starts_with("TY  - JOUR\nID  - pritchard2019a\nAU  - Pritchard, Benjamin P.\n"));
+        assert!(
+            txt.contains("T2  - J. Chem. Phys.\nVL  - 51\nSP  - 2657\nEP  - 2664\nPY  - 1969\n")
+        );
+        assert_eq!(txt.matches("ER  - \n").count(), 2);
+    }
+}
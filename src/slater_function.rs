@@ -0,0 +1,354 @@
+use crate::angular_momentum::AngularMomentum;
+use crate::basis_function::{BasisFunction, FunctionType, OriginalValues};
+use crate::error::AtoError;
+use std::f64::consts::PI;
+
+/// A contracted shell of Slater-type orbitals (`sto` on the BSE).
+///
+/// The radial part of the i-th primitive is `r^(n_i - 1) * exp(-z_i * r)`, where `n_i` are the
+/// `principal_numbers` and `z_i` the `exponents`. Like the Gaussian primitives, the
+/// primitives are normalized and the angular part is a spherical harmonic.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlaterFunction {
+    pub l: AngularMomentum,
+    pub principal_numbers: Vec<u8>,
+    pub exponents: Vec<f64>,
+    pub coefficients: Vec<f64>,
+    /// Region of the shell in the BSE data, e.g. `valence` or `polarization`.
+    pub region: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) original: Option<OriginalValues>,
+}
+
+impl SlaterFunction {
+    pub fn new(
+        l: AngularMomentum,
+        principal_numbers: Vec<u8>,
+        exponents: Vec<f64>,
+        coefficients: Vec<f64>,
+    ) -> Self {
+        Self {
+            l,
+            principal_numbers,
+            exponents,
+            coefficients,
+            region: String::new(),
+            original: None,
+        }
+    }
+
+    /// Number of atomic orbitals of the shell.
+    pub fn n_functions(&self) -> usize {
+        2 * self.l as usize + 1
+    }
+
+    /// Expand the shell into Gaussians.
+    ///
+    /// Every primitive is replaced by its STO-nG expansion with `n_gaussians` Gaussians, see
+    /// [sto_ng], so the contraction has `n_gaussians` times as many primitives.
+    pub fn to_gaussians(&self, n_gaussians: usize) -> Result<BasisFunction, AtoError> {
+        let mut exponents: Vec<f64> = Vec::new();
+        let mut coefficients: Vec<f64> = Vec::new();
+        for ((n, z), c) in self
+            .principal_numbers
+            .iter()
+            .zip(self.exponents.iter())
+            .zip(self.coefficients.iter())
+        {
+            let expansion = sto_ng(*n, self.l, *z, n_gaussians)?;
+            exponents.extend(expansion.exponents);
+            coefficients.extend(expansion.coefficients.iter().map(|d| c * d));
+        }
+        let mut function =
+            BasisFunction::new(self.l, exponents, coefficients, FunctionType::Spherical);
+        function.region = self.region.clone();
+        Ok(function)
+    }
+}
+
+/// Least-squares expansion of a normalized Slater function into `n_gaussians` Gaussians.
+///
+/// The Gaussians `r^l * exp(-a_i * r^2)` have the same angular part as the Slater function,
+/// their exponents and coefficients minimize the squared distance between the radial
+/// functions (STO-nG, as by Hehre, Stewart and Pople). The exponents are sorted in descending
+/// order and the coefficients belong to normalized primitives.
+///
+/// ```
+/// use ato::angular_momentum::AngularMomentum;
+/// use ato::slater_function::sto_ng;
+/// let function = sto_ng(1, AngularMomentum::S, 1.24, 3).unwrap();
+/// assert!((function.exponents[0] - 3.42525).abs() < 1e-4);
+/// ```
+pub fn sto_ng(
+    n: u8,
+    l: AngularMomentum,
+    exponent: f64,
+    n_gaussians: usize,
+) -> Result<BasisFunction, AtoError> {
+    if (n as usize) <= l as usize {
+        return Err(AtoError::InvalidArgument(format!(
+            "the principal quantum number {} is too small for the angular momentum {}",
+            n, l
+        )));
+    }
+    if !(exponent > 0.0 && exponent.is_finite()) {
+        return Err(AtoError::InvalidArgument(format!(
+            "the exponent {} of a Slater function must be positive",
+            exponent
+        )));
+    }
+    if n_gaussians == 0 {
+        return Err(AtoError::InvalidArgument(
+            "at least one Gaussian is needed for the expansion".to_owned(),
+        ));
+    }
+
+    // The fit is done for the exponent 1, the Gaussian exponents scale with its square.
+    let fit = Fit::new(n as usize, l as usize);
+    let mut x: Vec<f64> = if n_gaussians == 1 {
+        vec![(0.3f64).ln()]
+    } else {
+        // An even-tempered start, i.e. a geometric series of exponents.
+        let step = 3f64.ln();
+        let start = minimize(
+            |p| fit.residual(&even_tempered(p[0], p[1], n_gaussians)),
+            vec![(0.1f64).ln(), step],
+        );
+        even_tempered(start[0], start[1], n_gaussians)
+    };
+    // Restarting the simplex avoids that it gets stuck.
+    for _ in 0..3 {
+        x = minimize(|p| fit.residual(p), x);
+    }
+
+    let alphas: Vec<f64> = x.iter().map(|x| x.exp()).collect();
+    let (coefficients, _) = fit
+        .coefficients(&alphas)
+        .ok_or_else(|| AtoError::InvalidArgument("the expansion did not converge".to_owned()))?;
+    let mut primitives: Vec<(f64, f64)> = alphas
+        .iter()
+        .map(|a| a * exponent * exponent)
+        .zip(coefficients)
+        .collect();
+    primitives.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+    Ok(BasisFunction::new(
+        l,
+        primitives.iter().map(|p| p.0).collect(),
+        primitives.iter().map(|p| p.1).collect(),
+        FunctionType::Spherical,
+    ))
+}
+
+/// Logarithms of the exponents `a * b^i`.
+fn even_tempered(ln_a: f64, ln_b: f64, n: usize) -> Vec<f64> {
+    (0..n).map(|i| ln_a + i as f64 * ln_b).collect()
+}
+
+/// The least-squares problem of a Slater function with the exponent 1.
+struct Fit {
+    n: usize,
+    l: usize,
+}
+
+impl Fit {
+    fn new(n: usize, l: usize) -> Self {
+        Self { n, l }
+    }
+
+    /// Overlap of two normalized Gaussians.
+    fn overlap(&self, a: f64, b: f64) -> f64 {
+        (2.0 * (a * b).sqrt() / (a + b)).powf(self.l as f64 + 1.5)
+    }
+
+    /// Overlap of a normalized Gaussian with the normalized Slater function.
+    ///
+    /// The radial integral has no closed form, it is evaluated by the trapezoidal rule on a
+    /// logarithmic grid, which converges fast for smooth functions that decay at both ends.
+    fn projection(&self, a: f64) -> f64 {
+        let (n, l) = (self.n as i32, self.l as i32);
+        // Gamma(l + 3/2) = sqrt(pi) (2l + 1)!! / 2^(l + 1)
+        let double_factorial: f64 = (1..=2 * l + 1).step_by(2).map(f64::from).product();
+        let gamma = PI.sqrt() * double_factorial / 2f64.powi(l + 1);
+        let norm_gaussian = (2.0 * (2.0 * a).powf(l as f64 + 1.5) / gamma).sqrt();
+        let factorial: f64 = (1..=2 * n).map(f64::from).product();
+        let norm_slater = (2f64.powi(2 * n + 1) / factorial).sqrt();
+
+        const POINTS: usize = 1500;
+        let (start, end) = ((1e-8f64).ln(), (300f64).ln());
+        let h = (end - start) / POINTS as f64;
+        let integral: f64 = (0..=POINTS)
+            .map(|i| {
+                let r = (start + i as f64 * h).exp();
+                let weight = if i == 0 || i == POINTS { 0.5 } else { 1.0 };
+                weight * r.powi(n + l + 2) * (-a * r * r - r).exp()
+            })
+            .sum::<f64>()
+            * h;
+        norm_gaussian * norm_slater * integral
+    }
+
+    /// Optimal coefficients for the given exponents and the overlaps of the expansion with the
+    /// Slater function, `None` if the exponents are linearly dependent.
+    fn coefficients(&self, alphas: &[f64]) -> Option<(Vec<f64>, Vec<f64>)> {
+        let overlap: Vec<Vec<f64>> = alphas
+            .iter()
+            .map(|a| alphas.iter().map(|b| self.overlap(*a, *b)).collect())
+            .collect();
+        let projections: Vec<f64> = alphas.iter().map(|a| self.projection(*a)).collect();
+        solve(overlap, projections.clone()).map(|c| (c, projections))
+    }
+
+    /// Squared distance between the Slater function and its best expansion with the
+    /// logarithms of the exponents `x`.
+    fn residual(&self, x: &[f64]) -> f64 {
+        let alphas: Vec<f64> = x.iter().map(|x| x.exp()).collect();
+        if alphas.iter().any(|a| !a.is_normal()) {
+            return 1.0;
+        }
+        match self.coefficients(&alphas) {
+            Some((c, projections)) => {
+                1.0 - c.iter().zip(projections).map(|(c, p)| c * p).sum::<f64>()
+            }
+            None => 1.0,
+        }
+    }
+}
+
+/// Solve a symmetric positive definite system by the Cholesky decomposition.
+fn solve(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+    let size = rhs.len();
+    for j in 0..size {
+        let diagonal = matrix[j][j] - (0..j).map(|k| matrix[j][k].powi(2)).sum::<f64>();
+        if diagonal <= 1e-14 {
+            return None;
+        }
+        matrix[j][j] = diagonal.sqrt();
+        for i in j + 1..size {
+            let value = matrix[i][j] - (0..j).map(|k| matrix[i][k] * matrix[j][k]).sum::<f64>();
+            matrix[i][j] = value / matrix[j][j];
+        }
+    }
+    for i in 0..size {
+        rhs[i] = (rhs[i] - (0..i).map(|k| matrix[i][k] * rhs[k]).sum::<f64>()) / matrix[i][i];
+    }
+    for i in (0..size).rev() {
+        rhs[i] =
+            (rhs[i] - (i + 1..size).map(|k| matrix[k][i] * rhs[k]).sum::<f64>()) / matrix[i][i];
+    }
+    Some(rhs)
+}
+
+/// Minimize a function with the simplex method of Nelder and Mead.
+fn minimize<F: Fn(&[f64]) -> f64>(f: F, start: Vec<f64>) -> Vec<f64> {
+    let dim = start.len();
+    let mut simplex: Vec<(Vec<f64>, f64)> = vec![(start.clone(), f(&start))];
+    for i in 0..dim {
+        let mut x = start.clone();
+        x[i] += 0.5;
+        let value = f(&x);
+        simplex.push((x, value));
+    }
+    let combine = |a: &[f64], b: &[f64], t: f64| -> Vec<f64> {
+        a.iter().zip(b).map(|(a, b)| a + t * (b - a)).collect()
+    };
+
+    for _ in 0..5000 * dim {
+        simplex.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        if simplex[dim].1 - simplex[0].1 < 1e-15 {
+            break;
+        }
+        let centroid: Vec<f64> = (0..dim)
+            .map(|i| simplex[..dim].iter().map(|p| p.0[i]).sum::<f64>() / dim as f64)
+            .collect();
+        let worst = simplex[dim].clone();
+        let reflected = combine(&centroid, &worst.0, -1.0);
+        let reflected_value = f(&reflected);
+        if reflected_value < simplex[0].1 {
+            let expanded = combine(&centroid, &worst.0, -2.0);
+            let expanded_value = f(&expanded);
+            simplex[dim] = if expanded_value < reflected_value {
+                (expanded, expanded_value)
+            } else {
+                (reflected, reflected_value)
+            };
+        } else if reflected_value < simplex[dim - 1].1 {
+            simplex[dim] = (reflected, reflected_value);
+        } else {
+            let contracted = combine(&centroid, &worst.0, 0.5);
+            let contracted_value = f(&contracted);
+            if contracted_value < worst.1 {
+                simplex[dim] = (contracted, contracted_value);
+            } else {
+                let best = simplex[0].0.clone();
+                for point in simplex.iter_mut().skip(1) {
+                    point.0 = combine(&best, &point.0, 0.5);
+                    point.1 = f(&point.0);
+                }
+            }
+        }
+    }
+    simplex.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    simplex.swap_remove(0).0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::angular_momentum::AngularMomentum;
+    use crate::error::AtoError;
+    use crate::slater_function::{sto_ng, Fit, SlaterFunction};
+
+    #[test]
+    fn test_sto_3g() {
+        // Hydrogen 1s of STO-3G with the exponent 1.24.
+        let function = sto_ng(1, AngularMomentum::S, 1.24, 3).unwrap();
+        let exponents = [3.42525091, 0.62391373, 0.16885540];
+        let coefficients = [0.15432897, 0.53532814, 0.44463454];
+        for i in 0..3 {
+            assert!((function.exponents[i] / exponents[i] - 1.0).abs() < 1e-4);
+            assert!((function.coefficients[i] - coefficients[i]).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_convergence() {
+        // The exponent of STO-1G has the largest overlap with the 1s function.
+        let fit = Fit::new(1, 0);
+        assert!(fit.projection(0.270950) > fit.projection(0.26));
+        assert!(fit.projection(0.270950) > fit.projection(0.28));
+
+        let mut last = 1.0;
+        for n_gaussians in 1..=4 {
+            let function = sto_ng(3, AngularMomentum::D, 2.0, n_gaussians).unwrap();
+            assert_eq!(function.exponents.len(), n_gaussians);
+            let x: Vec<f64> = function.exponents.iter().map(|e| (e / 4.0).ln()).collect();
+            let residual = Fit::new(3, 2).residual(&x);
+            assert!(residual < last);
+            last = residual;
+        }
+        assert!(last < 1e-4);
+    }
+
+    #[test]
+    fn test_to_gaussians() {
+        let slater = SlaterFunction::new(
+            AngularMomentum::P,
+            vec![2, 3],
+            vec![1.5, 0.8],
+            vec![0.7, 0.4],
+        );
+        assert_eq!(slater.n_functions(), 3);
+        let function = slater.to_gaussians(2).unwrap();
+        assert_eq!(function.exponents.len(), 4);
+        let single = sto_ng(3, AngularMomentum::P, 0.8, 2).unwrap();
+        assert_eq!(function.exponents[2], single.exponents[0]);
+        assert!((function.coefficients[3] - 0.4 * single.coefficients[1]).abs() < 1e-12);
+
+        assert!(matches!(
+            sto_ng(1, AngularMomentum::P, 1.0, 3),
+            Err(AtoError::InvalidArgument(_))
+        ));
+        assert!(sto_ng(1, AngularMomentum::S, -1.0, 3).is_err());
+        assert!(sto_ng(1, AngularMomentum::S, 1.0, 0).is_err());
+    }
+}
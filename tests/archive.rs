@@ -0,0 +1,96 @@
+mod common;
+
+use ato::archive::{export_data, import_data, read_archive_manifest, ArchiveSelection};
+use ato::basis_set::BasisSet;
+use ato::bse::http::BasisSetExchange;
+use ato::files::{basis_path, data_exists, ensure_data_exist_from};
+use ato::manifest::Manifest;
+use common::{temporary_data_path, FixtureServer};
+use std::fs;
+use std::time::Duration;
+
+/// Export the downloaded fixture mirror and switch to an empty data directory.
+fn export(path: &std::path::Path, selection: &ArchiveSelection) -> std::path::PathBuf {
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+    ensure_data_exist_from(&bse).unwrap();
+    let archive = path.with_extension("tar");
+    export_data(&archive, selection).unwrap();
+    std::env::set_var("ATO_DATA_PATH", path.join("imported"));
+    archive
+}
+
+#[test]
+fn test_export_and_import() {
+    let (_guard, path) = temporary_data_path("archive");
+    let archive = export(&path, &ArchiveSelection::new());
+    let manifest = read_archive_manifest(&archive).unwrap();
+    assert_eq!(
+        manifest.basis_sets.keys().collect::<Vec<_>>(),
+        vec!["fixture-sz", "sto-2g"]
+    );
+    assert!(manifest
+        .files
+        .iter()
+        .any(|file| file.path == "basis_sets/sto-2g.json" && file.sha256.len() == 64));
+
+    assert_eq!(import_data(&archive).unwrap(), manifest);
+    assert!(data_exists().unwrap());
+    assert_eq!(Manifest::read().unwrap().basis_sets, manifest.basis_sets);
+
+    // The imported data is used without access to the BSE.
+    let offline = BasisSetExchange::builder()
+        .base_url("http://127.0.0.1:9")
+        .retries(0)
+        .timeout(Duration::from_millis(100))
+        .build()
+        .unwrap();
+    let basis = BasisSet::from_source(&offline, "STO-2G").unwrap();
+    assert_eq!(basis.name, "STO-2G");
+    fs::remove_file(&archive).unwrap();
+}
+
+#[test]
+fn test_export_selection() {
+    let (_guard, path) = temporary_data_path("archive");
+    let archive = export(&path, &ArchiveSelection::new().family("FIXTURE"));
+    let manifest = import_data(&archive).unwrap();
+    assert_eq!(
+        manifest.basis_sets.keys().collect::<Vec<_>>(),
+        vec!["fixture-sz"]
+    );
+    assert!(basis_path("fixture-sz").unwrap().exists());
+    assert!(!basis_path("sto-2g").unwrap().exists());
+    // Only a part of the basis sets of the metadata is present.
+    assert!(!data_exists().unwrap());
+
+    // Basis sets that are selected by name have to be present.
+    assert!(export_data(&archive, &ArchiveSelection::new().name("STO-2G")).is_err());
+    assert!(export_data(&archive, &ArchiveSelection::new().name("unknown")).is_err());
+    fs::remove_file(&archive).unwrap();
+}
+
+#[test]
+fn test_reject_corrupted_archive() {
+    let (_guard, path) = temporary_data_path("archive");
+    let archive = export(&path, &ArchiveSelection::new().name("sto-2g"));
+
+    // Change the exponent of the basis set without changing the size of the archive.
+    let mut data = fs::read(&archive).unwrap();
+    let position = data
+        .windows(4)
+        .position(|window| window == b"1.24")
+        .unwrap();
+    data[position + 3] = b'5';
+    fs::write(&archive, &data).unwrap();
+
+    let err = import_data(&archive).unwrap_err();
+    assert!(format!("{:#}", err).contains("checksum of basis_sets/sto-2g.json"));
+    // Nothing is imported from a corrupted archive.
+    assert!(!basis_path("sto-2g").unwrap().exists());
+    assert!(Manifest::read().unwrap().basis_sets.is_empty());
+    fs::remove_file(&archive).unwrap();
+}
@@ -0,0 +1,236 @@
+#![allow(dead_code)]
+//! A minimal HTTP server that serves a fake Basis Set Exchange for the integration tests.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+
+pub const METADATA: &str = r#"
+{
+    "sto-2g": {
+        "auxiliaries": {},
+        "basename": "STO-2G",
+        "description": "STO-2G Minimal Basis (2 functions/AO)",
+        "display_name": "STO-2G",
+        "family": "sto",
+        "function_types": ["gto"],
+        "latest_version": "1",
+        "notes_exist": [false, false],
+        "other_names": [],
+        "relpath": "",
+        "role": "orbital",
+        "tags": [],
+        "versions": {
+            "1": {
+                "elements": ["1"],
+                "file_relpath": "STO-2G.1.table.json",
+                "revdate": "2018-06-19",
+                "revdesc": "Data from Gaussian09"
+            }
+        }
+    },
+    "fixture-sz": {
+        "auxiliaries": {},
+        "basename": "Fixture-SZ",
+        "description": "Single zeta fixture",
+        "display_name": "Fixture-SZ",
+        "family": "fixture",
+        "function_types": ["gto"],
+        "latest_version": "0",
+        "notes_exist": [false, false],
+        "other_names": [],
+        "relpath": "",
+        "role": "orbital",
+        "tags": [],
+        "versions": {
+            "0": {
+                "elements": ["1"],
+                "file_relpath": "Fixture-SZ.0.table.json",
+                "revdate": "2020-01-01",
+                "revdesc": "Initial version"
+            }
+        }
+    }
+}"#;
+
+/// BSE JSON of a basis set with a single s function on hydrogen.
+pub fn basis_json(name: &str, version: &str, exponent: &str) -> String {
+    format!(
+        r#"{{
+        "molssi_bse_schema": {{"schema_type": "complete", "schema_version": "0.1"}},
+        "revision_description": "",
+        "revision_date": "",
+        "elements": {{
+            "1": {{
+                "electron_shells": [
+                    {{
+                        "function_type": "gto",
+                        "region": "",
+                        "angular_momentum": [0],
+                        "exponents": ["{}"],
+                        "coefficients": [["1.0"]]
+                    }}
+                ],
+                "references": []
+            }}
+        }},
+        "version": "{}",
+        "function_types": ["gto"],
+        "names": ["{}"],
+        "tags": [],
+        "family": "fixture",
+        "description": "",
+        "role": "orbital",
+        "auxiliaries": {{}},
+        "name": "{}"
+    }}"#,
+        exponent, version, name, name
+    )
+}
+
+/// A request that was received by the [FixtureServer].
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub path: String,
+    pub headers: HashMap<String, String>,
+}
+
+/// Fake BSE that answers the metadata and basis set endpoints from a fixed set of routes.
+pub struct FixtureServer {
+    pub url: String,
+    pub requests: Arc<Mutex<Vec<Request>>>,
+    pub routes: Arc<Mutex<HashMap<String, (u16, String)>>>,
+    pub failures: Arc<Mutex<HashMap<String, usize>>>,
+}
+
+impl FixtureServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests: Arc<Mutex<Vec<Request>>> = Arc::new(Mutex::new(Vec::new()));
+        let routes: Arc<Mutex<HashMap<String, (u16, String)>>> =
+            Arc::new(Mutex::new(HashMap::new()));
+        routes
+            .lock()
+            .unwrap()
+            .insert("/api/metadata/".to_owned(), (200, METADATA.to_owned()));
+        for (id, name, version) in [("sto-2g", "STO-2G", "1"), ("fixture-sz", "Fixture-SZ", "0")] {
+            routes.lock().unwrap().insert(
+                format!("/api/basis/{}/format/json/", id),
+                (200, basis_json(name, version, "1.24")),
+            );
+        }
+        let failures: Arc<Mutex<HashMap<String, usize>>> = Arc::new(Mutex::new(HashMap::new()));
+        let server_requests = Arc::clone(&requests);
+        let server_routes = Arc::clone(&routes);
+        let server_failures = Arc::clone(&failures);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let requests = Arc::clone(&server_requests);
+                let routes = Arc::clone(&server_routes);
+                let failures = Arc::clone(&server_failures);
+                thread::spawn(move || handle(stream, requests, routes, failures));
+            }
+        });
+        Self {
+            url,
+            requests,
+            routes,
+            failures,
+        }
+    }
+
+    /// Set the status and body that is returned for a path.
+    pub fn route(&self, path: &str, status: u16, body: &str) {
+        self.routes
+            .lock()
+            .unwrap()
+            .insert(path.to_owned(), (status, body.to_owned()));
+    }
+
+    /// Answer the next `times` requests for a path with "503 Service Unavailable".
+    pub fn fail(&self, path: &str, times: usize) {
+        self.failures.lock().unwrap().insert(path.to_owned(), times);
+    }
+
+    /// Number of requests for a path.
+    pub fn hits(&self, path: &str) -> usize {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.path == path)
+            .count()
+    }
+}
+
+fn handle(
+    mut stream: TcpStream,
+    requests: Arc<Mutex<Vec<Request>>>,
+    routes: Arc<Mutex<HashMap<String, (u16, String)>>>,
+    failures: Arc<Mutex<HashMap<String, usize>>>,
+) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut line = String::new();
+    if reader.read_line(&mut line).is_err() {
+        return;
+    }
+    // The BSE urls end with a bare "?", unless a version is requested.
+    let target = line.split_whitespace().nth(1).unwrap_or("");
+    let path = target.trim_end_matches('?').to_owned();
+    let mut headers: HashMap<String, String> = HashMap::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).is_err() || header.trim().is_empty() {
+            break;
+        }
+        if let Some((key, value)) = header.split_once(':') {
+            headers.insert(key.trim().to_lowercase(), value.trim().to_owned());
+        }
+    }
+    requests.lock().unwrap().push(Request {
+        path: path.clone(),
+        headers,
+    });
+    let failure = match failures.lock().unwrap().get_mut(&path) {
+        Some(remaining) if *remaining > 0 => {
+            *remaining -= 1;
+            true
+        }
+        _ => false,
+    };
+    let (status, body) = if failure {
+        (503, "Service Unavailable".to_owned())
+    } else {
+        routes
+            .lock()
+            .unwrap()
+            .get(&path)
+            .cloned()
+            .unwrap_or((404, "Not Found".to_owned()))
+    };
+    let response = format!(
+        "HTTP/1.1 {} Fixture\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes());
+}
+
+static DATA_PATH: Mutex<()> = Mutex::new(());
+
+/// Point $ATO_DATA_PATH to an empty directory.
+///
+/// The environment is shared by all tests of a process, so the returned guard serializes the
+/// tests that use the data directory.
+pub fn temporary_data_path(name: &str) -> (MutexGuard<'static, ()>, PathBuf) {
+    let guard = DATA_PATH.lock().unwrap_or_else(|err| err.into_inner());
+    let path = std::env::temp_dir().join(format!("ato_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    std::env::set_var("ATO_DATA_PATH", &path);
+    (guard, path)
+}
@@ -0,0 +1,286 @@
+mod common;
+
+use ato::angular_momentum::AngularMomentum;
+use ato::basis_function::{BasisFunction, FunctionType};
+use ato::basis_set::BasisSet;
+use ato::bse::http::BasisSetExchange;
+use ato::bse::source::BasisSource;
+use ato::catalogue::{Catalogue, CatalogueFilter};
+use ato::elements::Element;
+use ato::error::AtoError;
+use ato::files::{basis_path, ensure_data_exist_from, references_path, CUSTOM_SUBDIRECTORY};
+use ato::formats::{BasisFormat, BseJson};
+use ato::references::CitationFormat;
+use common::{basis_json, temporary_data_path, FixtureServer, METADATA};
+
+#[test]
+fn test_load_from_local_endpoint() {
+    let (_guard, _path) = temporary_data_path("download");
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .user_agent("ato-test")
+        .build()
+        .unwrap();
+
+    let basis = BasisSet::from_source(&bse, "STO-2G").unwrap();
+    assert_eq!(basis.name, "STO-2G");
+    assert_eq!(basis.basis_functions.get(&Element::H).unwrap().len(), 1);
+    assert!(basis_path("sto-2g").unwrap().exists());
+    // Only the requested basis set is downloaded.
+    assert!(!basis_path("fixture-sz").unwrap().exists());
+
+    // The second request is served from the cache.
+    BasisSet::from_source(&bse, "sto-2g").unwrap();
+    assert_eq!(server.hits("/api/metadata/"), 1);
+    assert_eq!(server.hits("/api/basis/sto-2g/format/json/"), 1);
+
+    let requests = server.requests.lock().unwrap();
+    assert!(requests
+        .iter()
+        .all(|request| request.headers.get("user-agent").map(String::as_str) == Some("ato-test")));
+}
+
+#[test]
+fn test_custom_basis_set() {
+    let (_guard, path) = temporary_data_path("download");
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+
+    // A diffuse function is added to STO-2G and stored as a custom basis set.
+    let mut basis = BasisSet::from_source(&bse, "STO-2G").unwrap();
+    basis.name = "STO-2G+".to_owned();
+    basis.names = vec!["STO-2G+".to_owned()];
+    basis
+        .basis_functions
+        .get_mut(&Element::H)
+        .unwrap()
+        .push(BasisFunction::new(
+            AngularMomentum::S,
+            vec![0.036],
+            vec![1.0],
+            FunctionType::Spherical,
+        ));
+    let custom = path.join(CUSTOM_SUBDIRECTORY);
+    std::fs::create_dir_all(&custom).unwrap();
+    std::fs::write(
+        custom.join("sto-2g-plus.json"),
+        BseJson.write(&basis).unwrap(),
+    )
+    .unwrap();
+
+    // The custom basis set is found without the BSE.
+    let basis = BasisSet::try_new("sto-2g+").unwrap();
+    assert_eq!(basis.id, "sto-2g-plus");
+    assert_eq!(basis.basis_functions.get(&Element::H).unwrap().len(), 2);
+    assert!(bse.names().unwrap().contains(&"STO-2G+".to_owned()));
+    // The basis sets of the BSE are still available.
+    assert!(BasisSet::from_source(&bse, "STO-2G").is_ok());
+
+    // A broken custom basis set is reported instead of falling back to the BSE.
+    std::fs::write(custom.join("broken.json"), "{").unwrap();
+    assert!(matches!(bse.load("STO-2G"), Err(AtoError::Parse(_))));
+    assert!(matches!(bse.names(), Err(AtoError::Parse(_))));
+    std::fs::remove_file(custom.join("broken.json")).unwrap();
+    assert!(bse.load("STO-2G").is_ok());
+}
+
+#[test]
+fn test_unknown_basis() {
+    let (_guard, _path) = temporary_data_path("download");
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+    assert!(matches!(
+        bse.load("cc-pVDZ"),
+        Err(AtoError::UnknownBasis { .. })
+    ));
+}
+
+#[test]
+fn test_env_endpoint() {
+    let (_guard, _path) = temporary_data_path("download");
+    let server = FixtureServer::start();
+    std::env::set_var("ATO_BSE_URL", format!("{}/", server.url));
+    let bse = BasisSetExchange::from_env().unwrap();
+    std::env::remove_var("ATO_BSE_URL");
+    assert_eq!(bse.base_url(), server.url);
+    assert_eq!(
+        bse.names().unwrap(),
+        vec!["fixture-sz".to_string(), "sto-2g".to_string()]
+    );
+}
+
+#[test]
+fn test_download_all() {
+    let (_guard, _path) = temporary_data_path("download");
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .concurrency(1)
+        .build()
+        .unwrap();
+    ensure_data_exist_from(&bse).unwrap();
+    assert!(basis_path("sto-2g").unwrap().exists());
+    assert!(basis_path("fixture-sz").unwrap().exists());
+    assert_eq!(server.hits("/api/basis/fixture-sz/format/json/"), 1);
+}
+
+#[test]
+fn test_server_error() {
+    let (_guard, _path) = temporary_data_path("download");
+    let server = FixtureServer::start();
+    server.route(
+        "/api/basis/sto-2g/format/json/",
+        500,
+        "Internal Server Error",
+    );
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .retries(0)
+        .build()
+        .unwrap();
+    assert!(matches!(
+        BasisSet::from_source(&bse, "sto-2g"),
+        Err(AtoError::Download(_))
+    ));
+    assert!(!basis_path("sto-2g").unwrap().exists());
+}
+
+#[test]
+fn test_catalogue_from_endpoint() {
+    let (_guard, _path) = temporary_data_path("download");
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+    let catalogue = Catalogue::from_exchange(&bse).unwrap();
+    let filter = CatalogueFilter::new().family("sto").elements(&[Element::H]);
+    let entries = catalogue.filter(&filter);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].name, "STO-2G");
+    assert_eq!(server.hits("/api/metadata/"), 1);
+}
+
+#[test]
+fn test_citations() {
+    let (_guard, _path) = temporary_data_path("download");
+    let server = FixtureServer::start();
+    let references = r#""references": [{
+        "reference_description": "STO-2G Minimal Basis (2 functions/AO)",
+        "reference_keys": ["hehre1969a"]
+    }]"#;
+    server.route(
+        "/api/basis/sto-2g/format/json/",
+        200,
+        &basis_json("STO-2G", "1", "1.24").replace(r#""references": []"#, references),
+    );
+    server.route(
+        "/api/references/sto-2g/format/json/",
+        200,
+        r#"[{
+            "reference_info": [{
+                "reference_description": "STO-2G Minimal Basis (2 functions/AO)",
+                "reference_data": [["hehre1969a", {
+                    "_entry_type": "article",
+                    "authors": ["Hehre, W. J.", "Stewart, R. F.", "Pople, J. A."],
+                    "title": "Self-Consistent Molecular-Orbital Methods. I.",
+                    "journal": "J. Chem. Phys.",
+                    "volume": "51",
+                    "pages": "2657-2664",
+                    "year": "1969",
+                    "doi": "10.1063/1.1672392"
+                }]]
+            }],
+            "elements": ["1"]
+        }]"#,
+    );
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+
+    let basis = BasisSet::from_source(&bse, "STO-2G").unwrap();
+    assert_eq!(
+        basis.reference_keys(&[Element::H, Element::H]).unwrap(),
+        vec!["hehre1969a".to_string()]
+    );
+    let bibtex = basis
+        .citations_from(&bse, &[Element::H, Element::H], CitationFormat::BibTex)
+        .unwrap();
+    assert!(bibtex.starts_with("@article{pritchard2019a,\n"));
+    assert_eq!(bibtex.matches("@article{hehre1969a,").count(), 1);
+
+    // The records are cached.
+    let ris = basis
+        .citations_from(&bse, &[Element::H], CitationFormat::Ris)
+        .unwrap();
+    assert!(ris.contains("ID  - hehre1969a\nAU  - Hehre, W. J.\n"));
+    assert_eq!(server.hits("/api/references/sto-2g/format/json/"), 1);
+
+    // A cache that lacks a key is downloaded again.
+    std::fs::write(references_path("sto-2g").unwrap(), "{}").unwrap();
+    let ris = basis
+        .citations_from(&bse, &[Element::H], CitationFormat::Ris)
+        .unwrap();
+    assert!(ris.contains("ID  - hehre1969a\n"));
+    assert_eq!(server.hits("/api/references/sto-2g/format/json/"), 2);
+
+    assert!(matches!(
+        basis.citations_from(&bse, &[Element::He], CitationFormat::Ris),
+        Err(AtoError::MissingElement { .. })
+    ));
+
+    // Basis sets that do not come from the BSE have no references there.
+    let mut custom = basis.clone();
+    custom.id = "sto-2g-plus".to_owned();
+    assert!(matches!(
+        custom.citations_from(&bse, &[Element::H], CitationFormat::Ris),
+        Err(AtoError::InvalidArgument(_))
+    ));
+    assert_eq!(server.hits("/api/references/sto-2g-plus/format/json/"), 0);
+}
+
+#[test]
+fn test_auxiliary() {
+    let (_guard, _path) = temporary_data_path("download");
+    let server = FixtureServer::start();
+    // Fixture-SZ is the JK fitting basis set of STO-2G.
+    let metadata = METADATA
+        .replace(
+            r#""auxiliaries": {},
+        "basename": "STO-2G""#,
+            r#""auxiliaries": {"jkfit": "Fixture-SZ"},
+        "basename": "STO-2G""#,
+        )
+        .replace(
+            r#""role": "orbital",
+        "tags": [],
+        "versions": {
+            "0""#,
+            r#""role": "jkfit",
+        "tags": [],
+        "versions": {
+            "0""#,
+        );
+    server.route("/api/metadata/", 200, &metadata);
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+
+    let basis = BasisSet::from_source(&bse, "STO-2G").unwrap();
+    let auxiliary = basis.auxiliary_from(&bse, "jkfit").unwrap();
+    assert_eq!(auxiliary.id, "fixture-sz");
+    assert_eq!(auxiliary.name, "Fixture-SZ");
+    assert!(matches!(
+        basis.auxiliary_from(&bse, "rifit"),
+        Err(AtoError::MissingAuxiliary { .. })
+    ));
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use ato::archive::{export_data, ArchiveSelection};
+use ato::basis_set::BasisSet;
+use ato::bse::http::BasisSetExchange;
+use ato::files::{
+    basis_path, data_exists, data_root, data_roots, find_data_path, update_data_from, JSON_METADATA,
+};
+use ato::manifest::Manifest;
+use common::{basis_json, temporary_data_path, FixtureServer, METADATA};
+use serde_json::{json, Value};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_layered_data_roots() {
+    let (_guard, path) = temporary_data_path("layers");
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+
+    // The shared library is prepared with a single root.
+    let shared = path.join("shared");
+    env::set_var("ATO_DATA_PATH", &shared);
+    BasisSet::from_source(&bse, "STO-2G").unwrap();
+
+    // A root below a regular file can not be written.
+    fs::write(path.join("file"), "").unwrap();
+    let blocked = path.join("file").join("data");
+    let user = path.join("user");
+    let roots = [blocked.clone(), user.clone(), shared.clone()];
+    env::set_var("ATO_DATA_PATH", env::join_paths(&roots).unwrap());
+    assert_eq!(data_roots().unwrap(), roots);
+    assert_eq!(data_root().unwrap(), user);
+
+    // Basis sets of the shared library are found without a download.
+    let basis = BasisSet::from_source(&bse, "STO-2G").unwrap();
+    assert_eq!(basis.name, "STO-2G");
+    assert_eq!(bse.locate("sto-2g").unwrap(), Some(shared.clone()));
+    assert_eq!(server.hits("/api/metadata/"), 1);
+    assert_eq!(server.hits("/api/basis/sto-2g/format/json/"), 1);
+
+    // Missing basis sets are downloaded to the writable root.
+    assert_eq!(bse.locate("Fixture-SZ").unwrap(), None);
+    BasisSet::from_source(&bse, "Fixture-SZ").unwrap();
+    assert_eq!(bse.locate("Fixture-SZ").unwrap(), Some(user.clone()));
+    assert!(basis_path("fixture-sz").unwrap().starts_with(&user));
+    assert!(!shared.join("basis_sets/fixture-sz.json").exists());
+    assert_eq!(
+        Manifest::read()
+            .unwrap()
+            .basis_sets
+            .keys()
+            .collect::<Vec<_>>(),
+        vec!["fixture-sz"]
+    );
+    assert!(data_exists().unwrap());
+
+    // An archive contains the basis sets of all layers.
+    let archive = path.join("all.tar");
+    let manifest = export_data(&archive, &ArchiveSelection::new()).unwrap();
+    assert_eq!(
+        manifest.basis_sets.keys().collect::<Vec<_>>(),
+        vec!["fixture-sz", "sto-2g"]
+    );
+}
+
+fn set_readonly(path: &Path, readonly: bool) {
+    let mut permissions = fs::metadata(path).unwrap().permissions();
+    permissions.set_readonly(readonly);
+    fs::set_permissions(path, permissions).unwrap();
+}
+
+#[test]
+fn test_shared_library_first() {
+    let (_guard, path) = temporary_data_path("layers");
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+
+    // The shared library contains the first version of STO-2G and is read-only afterwards.
+    let shared = path.join("shared");
+    env::set_var("ATO_DATA_PATH", &shared);
+    BasisSet::from_source(&bse, "STO-2G").unwrap();
+    set_readonly(&shared, true);
+
+    let home = path.join("home");
+    env::set_var("ATO_DATA_PATH", env::join_paths([&shared, &home]).unwrap());
+    assert_eq!(data_root().unwrap(), home);
+    // The writable root is only created by a download.
+    assert!(!home.exists());
+    assert_eq!(bse.locate("STO-2G").unwrap(), Some(shared.clone()));
+
+    // A new version is downloaded to the writable root by an update of the user.
+    let mut metadata: Value = serde_json::from_str(METADATA).unwrap();
+    metadata["sto-2g"]["latest_version"] = json!("2");
+    metadata["sto-2g"]["versions"]["2"] = json!({
+        "elements": ["1"],
+        "file_relpath": "STO-2G.2.table.json",
+        "revdate": "2022-02-02",
+        "revdesc": "Fixed exponents"
+    });
+    server.route("/api/metadata/", 200, &metadata.to_string());
+    server.route(
+        "/api/basis/sto-2g/format/json/",
+        200,
+        &basis_json("STO-2G", "2", "1.31"),
+    );
+    let summary = update_data_from(&bse).unwrap();
+    assert_eq!(summary.downloaded, vec!["sto-2g"]);
+    assert_eq!(summary.changes[0].old_version, "1");
+
+    // The updated copies of the user win over the stale ones of the shared library.
+    assert!(find_data_path(JSON_METADATA)
+        .unwrap()
+        .unwrap()
+        .starts_with(&home));
+    assert_eq!(bse.locate("STO-2G").unwrap(), Some(home.clone()));
+    let basis = BasisSet::from_source(&bse, "STO-2G").unwrap();
+    assert_eq!(basis.version, "2");
+    assert_eq!(
+        basis.basis_functions.values().next().unwrap()[0].exponents,
+        vec![1.31]
+    );
+    assert_eq!(server.hits("/api/basis/sto-2g/format/json/"), 2);
+    assert_eq!(
+        Manifest::read_from(&shared).unwrap().basis_sets["sto-2g"].version,
+        "1"
+    );
+    set_readonly(&shared, false);
+}
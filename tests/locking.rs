@@ -0,0 +1,88 @@
+mod common;
+
+use ato::archive::{export_data, ArchiveSelection};
+use ato::basis_set::BasisSet;
+use ato::bse::http::BasisSetExchange;
+use ato::elements::Element;
+use ato::files::{ensure_data_exist_from, DataLock};
+use ato::manifest::Manifest;
+use common::{temporary_data_path, FixtureServer};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_concurrent_first_use() {
+    let (_guard, _path) = temporary_data_path("locking");
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+
+    // Every thread opens the lock file on its own, like the jobs of a cluster.
+    thread::scope(|scope| {
+        let jobs: Vec<_> = (0..8)
+            .map(|_| scope.spawn(|| BasisSet::from_source(&bse, "STO-2G").unwrap()))
+            .collect();
+        for job in jobs {
+            assert_eq!(job.join().unwrap().basis_functions[&Element::H].len(), 1);
+        }
+    });
+    assert_eq!(server.hits("/api/metadata/"), 1);
+    assert_eq!(server.hits("/api/basis/sto-2g/format/json/"), 1);
+    assert!(Manifest::read().unwrap().is_complete("sto-2g").unwrap());
+}
+
+#[test]
+fn test_wait_for_lock() {
+    let (_guard, _path) = temporary_data_path("locking");
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+
+    let lock = DataLock::acquire().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            let summary = ensure_data_exist_from(&bse).unwrap();
+            sender.send(summary).unwrap();
+        });
+        // The download only starts once the lock is released.
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+        assert_eq!(server.hits("/api/metadata/"), 0);
+        drop(lock);
+        let summary = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(summary.downloaded, vec!["fixture-sz", "sto-2g"]);
+    });
+}
+
+#[test]
+fn test_export_waits_for_lock() {
+    let (_guard, path) = temporary_data_path("locking");
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+    ensure_data_exist_from(&bse).unwrap();
+
+    let archive = path.with_extension("tar");
+    let lock = DataLock::acquire().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            let manifest = export_data(&archive, &ArchiveSelection::new()).unwrap();
+            sender.send(manifest).unwrap();
+        });
+        // The files are not read while another process may replace them.
+        assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+        assert!(!archive.exists());
+        drop(lock);
+        let manifest = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(manifest.basis_sets.len(), 2);
+    });
+    std::fs::remove_file(&archive).unwrap();
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use ato::basis_set::BasisSet;
+use ato::bse::http::BasisSetExchange;
+use ato::files::{basis_path, data_exists, data_path, ensure_data_exist_from, JSON_METADATA};
+use ato::manifest::{Manifest, MANIFEST};
+use common::{temporary_data_path, FixtureServer};
+use std::fs;
+use std::time::Duration;
+
+const STO2G_PATH: &str = "/api/basis/sto-2g/format/json/";
+const FIXTURE_PATH: &str = "/api/basis/fixture-sz/format/json/";
+
+fn exchange(server: &FixtureServer, retries: u32) -> BasisSetExchange {
+    BasisSetExchange::builder()
+        .base_url(&server.url)
+        .retries(retries)
+        .backoff(Duration::from_millis(1))
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_retry_transient_errors() {
+    let (_guard, _path) = temporary_data_path("repair");
+    let server = FixtureServer::start();
+    server.fail("/api/metadata/", 1);
+    server.fail(STO2G_PATH, 2);
+
+    let summary = ensure_data_exist_from(&exchange(&server, 2)).unwrap();
+    assert!(summary.is_complete());
+    assert_eq!(summary.downloaded, vec!["fixture-sz", "sto-2g"]);
+    assert_eq!(server.hits("/api/metadata/"), 2);
+    assert_eq!(server.hits(STO2G_PATH), 3);
+    assert!(data_exists().unwrap());
+}
+
+#[test]
+fn test_report_and_repair_failures() {
+    let (_guard, _path) = temporary_data_path("repair");
+    let server = FixtureServer::start();
+    server.route(FIXTURE_PATH, 404, "Not Found");
+    let bse = exchange(&server, 3);
+
+    let summary = ensure_data_exist_from(&bse).unwrap();
+    assert!(!summary.is_complete());
+    assert_eq!(summary.downloaded, vec!["sto-2g"]);
+    assert_eq!(summary.failed.len(), 1);
+    assert_eq!(summary.failed[0].0, "fixture-sz");
+    // Client errors are not retried.
+    assert_eq!(server.hits(FIXTURE_PATH), 1);
+    assert!(!data_exists().unwrap());
+
+    // The second run only downloads the missing basis set.
+    server.route(
+        FIXTURE_PATH,
+        200,
+        &common::basis_json("Fixture-SZ", "0", "1.24"),
+    );
+    let summary = ensure_data_exist_from(&bse).unwrap();
+    assert!(summary.is_complete());
+    assert_eq!(summary.downloaded, vec!["fixture-sz"]);
+    assert_eq!(server.hits(STO2G_PATH), 1);
+    assert!(data_exists().unwrap());
+
+    // Nothing is downloaded once the data is complete.
+    let summary = ensure_data_exist_from(&bse).unwrap();
+    assert!(summary.downloaded.is_empty());
+}
+
+#[test]
+fn test_detect_partial_file() {
+    let (_guard, _path) = temporary_data_path("repair");
+    let server = FixtureServer::start();
+    let bse = exchange(&server, 0);
+    ensure_data_exist_from(&bse).unwrap();
+
+    // Simulate a file that was truncated by an interrupted download.
+    let path = basis_path("sto-2g").unwrap();
+    let content = fs::read(&path).unwrap();
+    fs::write(&path, &content[..content.len() / 2]).unwrap();
+    assert!(!Manifest::read().unwrap().is_complete("sto-2g").unwrap());
+    assert!(!data_exists().unwrap());
+
+    let summary = ensure_data_exist_from(&bse).unwrap();
+    assert_eq!(summary.downloaded, vec!["sto-2g"]);
+    assert_eq!(fs::read(&path).unwrap(), content);
+}
+
+#[test]
+fn test_data_without_manifest() {
+    let (_guard, _path) = temporary_data_path("repair");
+    let server = FixtureServer::start();
+    ensure_data_exist_from(&exchange(&server, 0)).unwrap();
+
+    // Older versions of this crate only wrote the dictionary of names and the basis sets.
+    fs::remove_file(data_path(MANIFEST).unwrap()).unwrap();
+    fs::remove_file(data_path(JSON_METADATA).unwrap()).unwrap();
+    let path = basis_path("fixture-sz").unwrap();
+    let content = fs::read(&path).unwrap();
+    fs::write(&path, &content[..content.len() / 2]).unwrap();
+
+    // The complete basis sets are used without access to the BSE.
+    let offline = BasisSetExchange::builder()
+        .base_url("http://127.0.0.1:9")
+        .retries(0)
+        .timeout(Duration::from_millis(100))
+        .build()
+        .unwrap();
+    let basis = BasisSet::from_source(&offline, "sto-2g").unwrap();
+    assert_eq!(basis.name, "STO-2G");
+    assert!(data_path(MANIFEST).unwrap().exists());
+    let manifest = Manifest::read().unwrap();
+    assert_eq!(manifest.basis_sets["sto-2g"].version, "1");
+    assert!(!manifest.basis_sets.contains_key("fixture-sz"));
+
+    // The truncated basis set is downloaded again.
+    let summary = ensure_data_exist_from(&exchange(&server, 0)).unwrap();
+    assert_eq!(summary.downloaded, vec!["fixture-sz"]);
+    assert_eq!(fs::read(&path).unwrap(), content);
+    assert_eq!(server.hits(STO2G_PATH), 1);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use ato::basis_set::BasisSet;
+use ato::bse::http::BasisSetExchange;
+use ato::bse::source::BasisSource;
+use ato::catalogue::Catalogue;
+use ato::files::{ensure_data_exist_from_async, update_data_from};
+use common::{temporary_data_path, FixtureServer};
+
+const STO2G_PATH: &str = "/api/basis/sto-2g/format/json/";
+
+fn exchange(server: &FixtureServer) -> BasisSetExchange {
+    BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap()
+}
+
+/// Runtime of an async application. The guard of the data path is held outside of it.
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_async_api() {
+    let (_guard, _path) = temporary_data_path("runtime");
+    let server = FixtureServer::start();
+    let bse = exchange(&server);
+    runtime().block_on(async_api(&server, &bse));
+}
+
+async fn async_api(server: &FixtureServer, bse: &BasisSetExchange) {
+    let basis = BasisSet::from_exchange_async(bse, "STO-2G").await.unwrap();
+    assert_eq!(basis.id, "sto-2g");
+    // The latest version is not downloaded again.
+    let pinned = BasisSet::from_exchange_with_version_async(bse, "STO-2G", "1")
+        .await
+        .unwrap();
+    assert_eq!(pinned.version, "1");
+    assert_eq!(server.hits(STO2G_PATH), 1);
+    assert!(bse.load_version_async("STO-2G", "7").await.is_err());
+
+    let catalogue = Catalogue::from_exchange_async(bse).await.unwrap();
+    assert_eq!(catalogue.len(), 2);
+    let summary = ensure_data_exist_from_async(bse).await.unwrap();
+    assert_eq!(summary.downloaded, vec!["fixture-sz"]);
+    assert_eq!(server.hits("/api/metadata/"), 1);
+}
+
+#[test]
+fn test_blocking_api_inside_runtime() {
+    let (_guard, _path) = temporary_data_path("runtime");
+    let server = FixtureServer::start();
+    let bse = exchange(&server);
+    runtime().block_on(blocking_api(&server, &bse));
+}
+
+async fn blocking_api(server: &FixtureServer, bse: &BasisSetExchange) {
+    // The downloads run on a separate thread instead of a nested runtime.
+    let basis = BasisSet::from_source(bse, "STO-2G").unwrap();
+    assert_eq!(basis.name, "STO-2G");
+    assert_eq!(bse.resolve("sto-2g").unwrap(), "sto-2g");
+    let summary = update_data_from(bse).unwrap();
+    assert!(summary.is_complete());
+    assert_eq!(server.hits("/api/metadata/"), 2);
+    assert_eq!(server.hits(STO2G_PATH), 1);
+}
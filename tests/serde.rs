@@ -0,0 +1,106 @@
+#![cfg(feature = "serde")]
+
+use ato::angular_momentum::AngularMomentum;
+use ato::atomic_orbital::AtomicOrbital;
+use ato::basis_function::FunctionType;
+use ato::basis_set::BasisSet;
+use ato::electron_shell::Shell;
+use ato::elements::Element;
+use ato::formats::gaussian94;
+
+const BASIS: &str = "\
+H     0
+S    2   1.00
+  13.01  0.0196850
+  1.962  0.1379770
+P    1   1.00
+  0.727  1.0
+****
+I     0
+I-ECP     1     28
+p potential
+  1
+2      1.0              0.0
+s-p potential
+  1
+2     40.0337391             49.9942930
+";
+
+#[test]
+fn test_representations() {
+    assert_eq!(serde_json::to_string(&Element::Fe).unwrap(), r#""Fe""#);
+    assert_eq!(
+        serde_json::from_str::<Element>(r#""fe""#).unwrap(),
+        Element::Fe
+    );
+    assert_eq!(serde_json::from_str::<Element>("26").unwrap(), Element::Fe);
+    assert!(serde_json::from_str::<Element>(r#""Xy""#).is_err());
+
+    assert_eq!(
+        serde_json::to_string(&AngularMomentum::D).unwrap(),
+        r#""d""#
+    );
+    assert_eq!(
+        serde_json::from_str::<AngularMomentum>(r#""D""#).unwrap(),
+        AngularMomentum::D
+    );
+    assert_eq!(
+        serde_json::from_str::<AngularMomentum>("2").unwrap(),
+        AngularMomentum::D
+    );
+    assert!(serde_json::from_str::<AngularMomentum>("11").is_err());
+
+    assert_eq!(serde_json::to_string(&Shell::Two).unwrap(), "2");
+    assert_eq!(serde_json::from_str::<Shell>("3").unwrap(), Shell::Three);
+    assert_eq!(
+        serde_json::to_string(&FunctionType::Cartesian).unwrap(),
+        r#""cartesian""#
+    );
+
+    let orbital = AtomicOrbital::create_2px();
+    let json = serde_json::to_string(&orbital).unwrap();
+    assert!(json.starts_with(r#"{"n":2,"l":"p","m":1,"#));
+    assert_eq!(
+        serde_json::from_str::<AtomicOrbital>(&json).unwrap(),
+        orbital
+    );
+}
+
+#[test]
+fn test_element_round_trip() {
+    for number in 0..Element::count() {
+        let element = Element::from(number);
+        let json = serde_json::to_string(&element).unwrap();
+        assert_eq!(json, format!(r#""{}""#, element.symbol()));
+        assert_eq!(serde_json::from_str::<Element>(&json).unwrap(), element);
+        let bytes = bincode::serialize(&element).unwrap();
+        assert_eq!(bincode::deserialize::<Element>(&bytes).unwrap(), element);
+    }
+}
+
+#[test]
+fn test_basis_set_json() {
+    let basis = gaussian94::read("test", BASIS).unwrap();
+    let json = serde_json::to_string(&basis).unwrap();
+    let again: BasisSet = serde_json::from_str(&json).unwrap();
+    assert_eq!(again.name, "test");
+    assert_eq!(again.basis_functions[&Element::H][1].l, AngularMomentum::P);
+    assert_eq!(again.ecp(Element::I).unwrap().core_electrons, 28);
+}
+
+#[test]
+fn test_basis_set_bincode() {
+    let basis = gaussian94::read("test", BASIS).unwrap();
+    let bytes = basis.to_bincode().unwrap();
+    assert!(bytes.len() < serde_json::to_vec(&basis).unwrap().len());
+    let again = BasisSet::from_bincode(&bytes).unwrap();
+    assert_eq!(
+        again.basis_functions[&Element::H][0].exponents,
+        basis.basis_functions[&Element::H][0].exponents
+    );
+    assert_eq!(
+        gaussian94::write(&again).unwrap(),
+        gaussian94::write(&basis).unwrap()
+    );
+    assert!(BasisSet::from_bincode(&bytes[..10]).is_err());
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use ato::basis_set::BasisSet;
+use ato::bse::http::BasisSetExchange;
+use ato::files::update_data_from;
+use ato::manifest::Manifest;
+use common::{basis_json, temporary_data_path, FixtureServer, METADATA};
+use serde_json::{json, Value};
+
+const STO2G_PATH: &str = "/api/basis/sto-2g/format/json/";
+
+/// Publish a new version of STO-2G on the fixture server.
+fn publish_version(server: &FixtureServer, version: &str, revdesc: &str) {
+    let mut metadata: Value = serde_json::from_str(METADATA).unwrap();
+    metadata["sto-2g"]["latest_version"] = json!(version);
+    metadata["sto-2g"]["versions"][version] = json!({
+        "elements": ["1"],
+        "file_relpath": format!("STO-2G.{}.table.json", version),
+        "revdate": "2022-02-02",
+        "revdesc": revdesc
+    });
+    server.route("/api/metadata/", 200, &metadata.to_string());
+    server.route(STO2G_PATH, 200, &basis_json("STO-2G", version, "1.31"));
+}
+
+#[test]
+fn test_update_changed_versions() {
+    let (_guard, _path) = temporary_data_path("update");
+    let server = FixtureServer::start();
+    let bse = BasisSetExchange::builder()
+        .base_url(&server.url)
+        .build()
+        .unwrap();
+    BasisSet::from_source(&bse, "sto-2g").unwrap();
+    assert_eq!(Manifest::read().unwrap().basis_sets["sto-2g"].version, "1");
+
+    // Nothing changed on the BSE.
+    let summary = update_data_from(&bse).unwrap();
+    assert!(summary.downloaded.is_empty());
+    assert!(summary.changes.is_empty());
+    assert_eq!(server.hits(STO2G_PATH), 1);
+
+    publish_version(&server, "2", "Fixed exponents");
+    let summary = update_data_from(&bse).unwrap();
+    assert_eq!(summary.downloaded, vec!["sto-2g"]);
+    assert_eq!(summary.changes.len(), 1);
+    let change = &summary.changes[0];
+    assert_eq!(change.id, "sto-2g");
+    assert_eq!(change.old_version, "1");
+    assert_eq!(change.new_version, "2");
+    assert_eq!(change.revision_description, "Fixed exponents");
+    // Basis sets that were never used are not downloaded by an update.
+    assert_eq!(server.hits("/api/basis/fixture-sz/format/json/"), 0);
+
+    let basis = BasisSet::from_source(&bse, "sto-2g").unwrap();
+    assert_eq!(
+        basis.basis_functions.values().next().unwrap()[0].exponents,
+        vec![1.31]
+    );
+    assert_eq!(Manifest::read().unwrap().basis_sets["sto-2g"].version, "2");
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use ato::basis_set::BasisSet;
+use ato::bse::http::BasisSetExchange;
+use ato::error::AtoError;
+use common::{basis_json, temporary_data_path, FixtureServer, METADATA};
+use serde_json::{json, Value};
+
+const STO2G_PATH: &str = "/api/basis/sto-2g/format/json/";
+
+fn exchange(server: &FixtureServer) -> BasisSetExchange {
+    BasisSetExchange::builder()
+        .base_url(&server.url)
+        .retries(0)
+        .build()
+        .unwrap()
+}
+
+/// List the older version 0 of STO-2G in the metadata.
+fn publish_old_version(server: &FixtureServer) {
+    let mut metadata: Value = serde_json::from_str(METADATA).unwrap();
+    metadata["sto-2g"]["versions"]["0"] = json!({
+        "elements": ["1"],
+        "file_relpath": "STO-2G.0.table.json",
+        "revdate": "2007-01-15",
+        "revdesc": "Data from the Original Basis Set Exchange"
+    });
+    server.route("/api/metadata/", 200, &metadata.to_string());
+}
+
+#[test]
+fn test_pinned_version() {
+    let (_guard, _path) = temporary_data_path("versions");
+    let server = FixtureServer::start();
+    publish_old_version(&server);
+    server.route(
+        &format!("{}?version=0", STO2G_PATH),
+        200,
+        &basis_json("STO-2G", "0", "1.11"),
+    );
+    let bse = exchange(&server);
+
+    let latest = BasisSet::from_source(&bse, "sto-2g").unwrap();
+    assert_eq!(latest.version, "1");
+    assert_eq!(latest.id, "sto-2g");
+
+    // The latest version is taken from the cache.
+    let pinned = BasisSet::from_source_with_version(&bse, "STO-2G", "1").unwrap();
+    assert_eq!(pinned.version, "1");
+    assert_eq!(server.hits(STO2G_PATH), 1);
+
+    let pinned = BasisSet::from_source_with_version(&bse, "STO-2G", "0").unwrap();
+    assert_eq!(pinned.version, "0");
+    assert_eq!(pinned.id, "sto-2g");
+    let exponents = &pinned.basis_functions.values().next().unwrap()[0].exponents;
+    assert_eq!(exponents, &vec![1.11]);
+
+    // Both versions are kept side by side.
+    BasisSet::from_source_with_version(&bse, "STO-2G", "0").unwrap();
+    assert_eq!(server.hits(&format!("{}?version=0", STO2G_PATH)), 1);
+    assert_eq!(BasisSet::from_source(&bse, "sto-2g").unwrap().version, "1");
+}
+
+#[test]
+fn test_unknown_version() {
+    let (_guard, _path) = temporary_data_path("versions");
+    let server = FixtureServer::start();
+    let bse = exchange(&server);
+    assert!(matches!(
+        BasisSet::from_source_with_version(&bse, "sto-2g", "7"),
+        Err(AtoError::UnknownVersion { .. })
+    ));
+}
+
+#[test]
+fn test_wrong_version_from_server() {
+    let (_guard, _path) = temporary_data_path("versions");
+    let server = FixtureServer::start();
+    // The server ignores the version and always answers with the latest one.
+    server.route(
+        &format!("{}?version=0", STO2G_PATH),
+        200,
+        &basis_json("STO-2G", "1", "1.24"),
+    );
+    publish_old_version(&server);
+    let bse = exchange(&server);
+    BasisSet::from_source(&bse, "sto-2g").unwrap();
+    assert!(BasisSet::from_source_with_version(&bse, "sto-2g", "0").is_err());
+}